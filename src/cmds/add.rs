@@ -1,99 +1,82 @@
-use crate::util::consts::{FMAP_ANGLE, FMAP_STANDARD, FREE_CHAR};
-use crate::util::core::{Layout, LayoutConfig};
-use crate::util::layout::check_name;
+use crate::util::core::{Layout, Position};
+use crate::util::layout::{apply_fingermap, get_fingermap, get_indents, parse_template};
 use crate::util::memory::LAYOUTS;
-use crate::util::parser::get_layout;
+use crate::util::parser::{get_kwargs, KwargType};
 use crate::util::{layout, Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("board".to_owned(), KwargType::Str),
+    ("fingers".to_owned(), KwargType::Str),
+]));
 
 pub struct Command;
 
 impl Commandable for Command {
     fn exec(&self, msg: &Message) -> String {
-        let (name, matrix) = get_layout(msg.arg);
-        if name.is_empty() {
-            return self.help();
-        }
-        if let Err(err) = check_name(&name) {
-            return err;
-        }
-        let rows = matrix.lines().collect::<Vec<_>>();
-
-        let row_count = rows.len();
-
-        if row_count < 3 {
-            return format!("Expected 3 lines, got {}", row_count);
-        }
-
-        // Calculate amount of leading whitespace for each line
-        let spaces = rows.iter().map(|row| {
-            row.chars().take_while(|c| c.is_whitespace()).count()
-        })
-            .collect::<Vec<_>>();
-
-        let mut max_rows = 3;
-
-        let board = if spaces[0] < spaces[1] && spaces[1] < spaces[2] {
-            "stagger".to_owned()
-        } else if spaces[0] == spaces[1] && spaces[2] > 1 {
-            max_rows = 4;
-            "mini".to_owned()
-        } else if spaces[0] == spaces[1] && spaces[1] < spaces[2] {
-            "angle".to_owned()
-        } else if spaces[0] == spaces[1] && spaces[1] == spaces[2] {
-            max_rows = 3;
-            "ortho".to_owned()
-        } else {
-            return "Error: board shape is undefined".to_owned();
+        let parsed = match parse_layout(msg.arg) {
+            Ok(Some(parsed)) => parsed,
+            Ok(None) => return self.help(),
+            Err(err) => return err,
         };
+        let name = parsed.name;
 
-        if row_count > max_rows {
-            return format!("Error: board type `{board}` supports at most {max_rows} rows, got {row_count}");
+        match layout::create(&name, msg.id, parsed.board, parsed.keys) {
+            Ok(()) => format!("Success!\n{}{}", parsed.board_note, layout::to_string(&LAYOUTS.get(&name), msg.id)),
+            Err(err) => err,
         }
+    }
 
-        let mut keymap: Layout = Layout::default();
-        for (row_idx, row) in rows[..3].iter().enumerate() {
-            for (col_idx, ch) in row
-                .chars()
-                .filter(|c| *c != ' ' && *c != FREE_CHAR)
-                .enumerate() {
-
-                let fmap = if row_idx == 2 && board == "angle" {
-                    &FMAP_ANGLE
-                } else {
-                    &FMAP_STANDARD
-                };
-
-                let finger = fmap[col_idx.min(9)];
+    fn usage<'a>(&self) -> &'a str {
+        "add <layout> [--board <board>] [--fingers <standard|angle>] ``\u{200b}`keys`\u{200b}`` [``\u{200b}`fingermap`\u{200b}``]"
+    }
 
-                if keymap.insert(ch, (row_idx as u8, col_idx as u8, finger)).is_some() {
-                    return format!("Error: `{ch}` is defined twice");
-                }
-            }
-        }
-        if max_rows == 4 {
-            if let Some(thumb_row) = rows.get(3) {
-                let finger = if spaces[3] > 8 { 4 } else { 5 };
-                for (i, ch) in thumb_row.chars().filter(|c| *c != ' ' && *c != FREE_CHAR).enumerate() {
-                    if keymap.insert(ch, (3, i as u8, finger)).is_some() {
-                        return format!("Error: `{ch}` is defined twice");
-                    }
-                }
-            }
-        }
+    fn desc<'a>(&self) -> &'a str {
+        "contribute a new layout, optionally with the finger of each key from 0 (LP) to 7 (RP), and 8 and 9 for the thumbs"
+    }
 
-        let data = LayoutConfig::new(name.clone(), msg.id, board, keymap);
-        if LAYOUTS.add(data) {
-            format!("Success!\n{}", layout::to_string(&LAYOUTS.get(&name), msg.id))
-        } else {
-            format!("Error: `{name}` already exists")
-        }
+    fn records_author(&self) -> bool {
+        true
     }
+}
 
-    fn usage<'a>(&self) -> &'a str {
-        "add <layout> ``\u{200b}`keys`\u{200b}``"
+/// Layout as written in `add`
+pub struct ParsedLayout {
+    pub name: String,
+    pub board: String,
+    pub keys: Layout,
+    /// Positions left without a key with `~`, sorted
+    pub free: Vec<Position>,
+    /// How the board was detected, empty if it was given
+    pub board_note: String,
+}
+
+/// Reads the name, matrix and fingering of a layout as written in `add`, or `None` if it has no name
+pub fn parse_layout(arg: &str) -> Result<Option<ParsedLayout>, String> {
+    let parts: Vec<&str> = arg.split("```").collect();
+    let kwargs = get_kwargs(parts[0], &KWARGS).map_err(|err| err.to_string())?;
+    let name = kwargs.arg.to_lowercase();
+    let matrix = parts.get(1).unwrap_or(&"").trim_end().to_lowercase();
+    if name.is_empty() {
+        return Ok(None);
     }
+    let board = kwargs["board"].unwrap_str().map(str::to_lowercase);
+    let (board, mut keys, mut free) = parse_template(&matrix, board.as_deref())?;
+    free.sort_unstable();
+    // Tell how the board was detected, so that a wrong guess is easy to spot
+    let board_note = match kwargs["board"].unwrap_str() {
+        Some(_) => String::new(),
+        None => {
+            let indents: Vec<String> = get_indents(&matrix).iter().map(usize::to_string).collect();
+            format!("Detected board `{board}` from the rows indented by {} spaces. \
+                     If that is wrong, fix it with `edit-board {name} <board>`\n", indents.join(", "))
+        }
+    };
 
-    fn desc<'a>(&self) -> &'a str {
-        "contribute a new layout"
+    if let Some(fingermap) = get_fingermap(kwargs["fingers"].unwrap_str(), parts.get(3).copied())? {
+        apply_fingermap(&mut keys, &fingermap)?;
     }
+    Ok(Some(ParsedLayout { name, board, keys, free, board_note }))
 }
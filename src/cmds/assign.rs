@@ -1,15 +1,26 @@
-use crate::util::authors::AUTHORS;
+use crate::util::authors::{is_discord_id, parse_mention, AUTHORS};
 use crate::util::memory::LAYOUTS;
-use crate::util::parser::split_word;
+use crate::util::parser::{get_kwargs, split_word, KwargType};
 use crate::util::{Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("name".to_owned(), KwargType::Str),
+]));
 
 pub struct Command;
 
 impl Commandable for Command {
     fn exec(&self, msg: &Message) -> String {
-        let mut arg = msg.arg;
+        let kwargs = match get_kwargs(msg.arg, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return err.to_string(),
+        };
+        let mut arg = kwargs.arg.as_str();
         let layout_name = split_word(&mut arg);
-        let mut author = arg;
+        let author = arg;
 
         if layout_name.is_empty() || author.is_empty() {
             return self.help();
@@ -17,29 +28,49 @@ impl Commandable for Command {
         if !LAYOUTS.contains(layout_name) {
             return format!("Error: `{layout_name}` does not exist");
         }
-        let authors = AUTHORS.read().unwrap();
-
-        let author_id = match author.parse::<u64>() {
-            // Assign using ID
-            Ok(author_id) => {
-                match authors.get_name(author_id) {
-                    None => return format!("Error: Author with ID `{author_id}` does not exist"),
-                    Some(match_author_name) => author = match_author_name,
-                }
-                author_id
+
+        let (author_id, author) = {
+            let mut authors = AUTHORS.write().unwrap();
+            if let Some(id) = author.parse::<u64>().ok().filter(|id| !is_discord_id(*id) && authors.get_name(*id).is_none()) {
+                return format!("Error: `{id}` is not a Discord ID, check it or mention the author");
             }
-            // Assign using name
-            Err(_) => authors.get_id(author),
+            // A typed number only makes a new author if it could be a Discord ID, so that typos don't
+            let new_id = parse_mention(author)
+                .or_else(|| author.parse::<u64>().ok().filter(|id| is_discord_id(*id)))
+                .filter(|id| authors.get_name(*id).is_none());
+            let author_id = match (new_id, kwargs["name"].unwrap_str()) {
+                // Assign to someone who has no layouts yet
+                (Some(id), name) => {
+                    let mentioned = msg.mentions.iter().find(|user| *user.id.as_u64() == id);
+                    let Some(name) = name.or(mentioned.map(|user| user.name.as_str())) else {
+                        return format!("Error: author with ID `{id}` is not known yet, \
+                                        mention them or give their name with `--name <name>`");
+                    };
+                    if authors.get_owner(name).is_some() {
+                        return format!("Error: `{name}` is the name of another author, \
+                                        give another name with `--name <name>`");
+                    }
+                    authors.update(id, name.to_owned());
+                    id
+                }
+                (None, Some(_)) => return "Error: `--name` is only for authors who are not known yet".to_owned(),
+                (None, None) => match authors.resolve(author) {
+                    Ok(id) => id,
+                    Err(err) => return err,
+                },
+            };
+            (author_id, authors.get_name(author_id).unwrap_or_default().to_owned())
         };
         {
             let layout = &mut*LAYOUTS.get_mut(layout_name);  // always contains layout
+            layout.push_revision(msg.id);
             layout.user = author_id;
             format!("`{layout_name}` has been assigned to `{author}`")
         }
     }
 
     fn usage<'a>(&self) -> &'a str {
-        "assign <layout> <author>"
+        "assign <layout> <author name|id|mention> [--name <name of a new author>]"
     }
 
     fn desc<'a>(&self) -> &'a str {
@@ -0,0 +1,68 @@
+use crate::util::authors::AUTHORS;
+use crate::util::consts::MESSAGE_LIMIT;
+use crate::util::corpora::{get_user_corpus, CORPORA_PREFS};
+use crate::util::memory::{LAYOUTS, LIKES};
+use crate::util::{Commandable, Message};
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let author = msg.arg.trim();
+        if author.is_empty() {
+            return self.help();
+        }
+        let (id, names) = {
+            let authors = AUTHORS.read().unwrap();
+            let id = match authors.resolve(author) {
+                Ok(id) => id,
+                Err(err) => return err,
+            };
+            (id, authors.get_names(id).unwrap_or_default().to_vec())
+        };
+
+        let mut layouts: Vec<(String, usize)> = {
+            let layouts = LAYOUTS.read().unwrap();
+            let likes = LIKES.read().unwrap();
+            layouts.values()
+                .filter(|ll| ll.user == id)
+                .map(|ll| (ll.name.clone(), likes.get(&ll.store_key()).map_or(0, Vec::len)))
+                .collect()
+        };
+        layouts.sort_by(|(name0, likes0), (name1, likes1)| likes1.cmp(likes0).then_with(|| name0.cmp(name1)));
+        let total_likes: usize = layouts.iter().map(|(_, likes)| likes).sum();
+
+        let mut s = format!("**{}** (ID `{id}`)\n", names[0]);
+        if names.len() > 1 {
+            s.push_str(&format!("Also known as: {}\n", names[1..].join(", ")));
+        }
+        let default_str = if CORPORA_PREFS.read().unwrap().contains_key(&id) { "" } else { " (default)" };
+        s.push_str(&format!("Corpus: {}{default_str}\n", get_user_corpus(id)));
+        let like_str = if total_likes == 1 { "like" } else { "likes" };
+        s.push_str(&format!("{} layouts, {total_likes} {like_str} in total", layouts.len()));
+        if layouts.is_empty() {
+            return s;
+        }
+        s.push_str(":\n");
+        // Most liked first, cut to fit in a message
+        for (index, (name, likes)) in layouts.iter().enumerate() {
+            let line = format!("`{name}` ({likes}), ");
+            let rest = layouts.len() - index;
+            if s.len() + line.len() + 20 > MESSAGE_LIMIT {
+                s.push_str(&format!("and {rest} more"));
+                return s;
+            }
+            s.push_str(&line);
+        }
+        s.truncate(s.len() - 2);
+        s
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "author <name|id|mention>"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "see the names, layouts and likes of an author"
+    }
+}
@@ -0,0 +1,42 @@
+use crate::util::consistency;
+use crate::util::parser::{get_kwargs, KwargType};
+use crate::util::{Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("repair".to_owned(), KwargType::Bool),
+]));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let kwargs = match get_kwargs(msg.arg, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return err.to_string(),
+        };
+        let issues = consistency::find_issues();
+        if issues.count() == 0 {
+            return "Likes, links and cached stats all belong to a layout".to_owned();
+        }
+        if !kwargs["repair"].unwrap_bool() {
+            return format!("Found {} issues:\n{issues}Help: fix them with `check-data --repair`", issues.count());
+        }
+        consistency::repair(&issues);
+        format!("Repaired {} issues:\n{issues}", issues.count())
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "check-data [--repair]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "find the likes, links and cached stats of missing layouts, and optionally remove them"
+    }
+
+    fn mods_only(&self) -> bool {
+        true
+    }
+}
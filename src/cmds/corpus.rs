@@ -1,11 +1,16 @@
 use crate::util::{Commandable, Message};
-use crate::util::corpora::{set_user_corpus, CORPORA};
+use crate::util::consts::STANDARD_KEYS;
+use crate::util::core::Key;
+use crate::util::corpora::{corpus_ngrams, get_user_corpus, merge_grams, set_user_corpus, CORPORA};
+use crate::util::parser::split_word;
+
+const TOP_COUNT: usize = 10;
 
 pub struct Command;
 
 impl Commandable for Command {
     fn exec(&self, msg: &Message) -> String {
-        let arg = &msg.arg;
+        let mut arg = msg.arg;
         if arg.is_empty() {
             let mut s = "```\nList of Corpora\n".to_owned();
             let corpora = CORPORA.as_slice();
@@ -17,6 +22,17 @@ impl Commandable for Command {
             s.push_str("```");
             return s;
         }
+        if arg.split_whitespace().next() == Some("info") {
+            split_word(&mut arg);
+            let corpus = match arg.is_empty() {
+                true => get_user_corpus(msg.id),
+                false => arg.to_lowercase(),
+            };
+            if !CORPORA.contains(&corpus) {
+                return format!("The corpus `{corpus}` doesn\'t exist.");
+            }
+            return get_info_str(&corpus);
+        }
         match set_user_corpus(msg.id, arg) {
             Ok(_) => format!("Your corpus preference has been changed to `{}`.", arg.to_lowercase()),
             Err(_) => format!("The corpus `{arg}` doesn\'t exist."),
@@ -24,10 +40,79 @@ impl Commandable for Command {
     }
 
     fn usage<'a>(&self) -> &'a str {
-        "corpus <corpus_name>"
+        "corpus <corpus_name>\n\
+         corpus info [corpus_name]"
     }
 
     fn desc<'a>(&self) -> &'a str {
-        "set your preferred corpus"
+        "set your preferred corpus, or see the statistics of a corpus"
+    }
+}
+
+fn get_info_str(corpus: &str) -> String {
+    let monograms = merge_grams(&corpus_ngrams::<1>(corpus));
+    let bigrams = merge_grams(&corpus_ngrams::<2>(corpus));
+    let trigrams = merge_grams(&corpus_ngrams::<3>(corpus));
+
+    let mono_total = monograms.iter().map(|(_, count)| count).sum::<u64>();
+    let bi_total = bigrams.iter().map(|(_, count)| count).sum::<u64>();
+    let tri_total = trigrams.iter().map(|(_, count)| count).sum::<u64>();
+
+    let uncovered = monograms.iter()
+        .filter(|(gram, _)| !STANDARD_KEYS.contains(gram[0]))
+        .map(|(_, count)| count)
+        .sum::<u64>();
+    let spaced = trigrams.iter()
+        .filter(|(gram, _)| gram.contains(&' '))
+        .map(|(_, count)| count)
+        .sum::<u64>();
+    let uncovered = percent(uncovered, mono_total);
+    let spaced = percent(spaced, tri_total);
+
+    let mono_count = monograms.len();
+    let bi_count = bigrams.len();
+    let tri_count = trigrams.len();
+    let corpus_name = corpus.to_uppercase();
+    let top_mono = get_top(&monograms, mono_total);
+    let top_bi = get_top(&bigrams, bi_total);
+    let top_tri = get_top(&trigrams, tri_total);
+
+    let mut top_str = String::new();
+    for index in 0..TOP_COUNT {
+        let get = |top: &[String]| top.get(index).cloned().unwrap_or_default();
+        top_str.push_str(&format!(
+            "{:<14}{:<14}{}\n", get(&top_mono), get(&top_bi), get(&top_tri)
+        ));
+    }
+
+    format!(
+        "```\n\
+         {corpus_name}\n\
+         \n\
+  Chars:     {mono_total}\n\
+  Distinct:  {mono_count} mono | {bi_count} bi | {tri_count} tri\n\
+  Uncovered: {uncovered:>5.2}%   (outside the standard 30 keys)\n\
+  Spaced:    {spaced:>5.2}%   (trigrams skipped for containing a space)\n\
+         \n\
+  Monograms     Bigrams       Trigrams\n\
+         {top_str}\
+         ```"
+    )
+}
+
+fn get_top<const N: usize>(grams: &[([Key; N], u64)], total: u64) -> Vec<String> {
+    grams.iter()
+        .take(TOP_COUNT)
+        .map(|(gram, count)| {
+            let gram: String = gram.iter().collect();
+            format!("{gram:<3} {:>5.2}%", percent(*count, total))
+        })
+        .collect()
+}
+
+fn percent(count: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
     }
-}
\ No newline at end of file
+    count as f64 / total as f64 * 100.0
+}
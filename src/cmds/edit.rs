@@ -0,0 +1,50 @@
+use crate::cmds::add::parse_layout;
+use crate::util::consts::ADMINS;
+use crate::util::memory::LAYOUTS;
+use crate::util::{cache, layout, Commandable, Message};
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let parsed = match parse_layout(msg.arg) {
+            Ok(Some(parsed)) => parsed,
+            Ok(None) => return self.help(),
+            Err(err) => return err,
+        };
+        let name = parsed.name;
+
+        let id = {
+            let mut ll = LAYOUTS.get_mut(&name);
+            let Some(ll) = ll.checked() else {
+                return format!("Error: `{name}` does not exist");
+            };
+            if ll.user != msg.id && !ADMINS.contains(&msg.id) {
+                return format!("Error: you don't own `{name}`");
+            }
+            if ll.keys == parsed.keys && ll.board == parsed.board && ll.free == parsed.free {
+                return format!("`{name}` is unchanged");
+            }
+            ll.push_revision(msg.id);
+            ll.board = parsed.board;
+            ll.set_keys(parsed.keys);
+            // The free positions of the old keys may be taken or gone, so they come from the new matrix
+            ll.set_free(parsed.free);
+            ll.id
+        };
+        cache::invalidate(id);
+        format!("`{name}` has been updated\n{}{}", parsed.board_note, layout::to_string(&LAYOUTS.get(&name), msg.id))
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "edit <layout> [--board <board>] [--fingers <standard|angle>] ``\u{200b}`keys`\u{200b}`` [``\u{200b}`fingermap`\u{200b}``]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "replace the keys of one of your layouts, keeping its likes and links"
+    }
+
+    fn records_author(&self) -> bool {
+        true
+    }
+}
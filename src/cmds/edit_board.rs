@@ -0,0 +1,51 @@
+use crate::util::consts::{ADMINS, BOARDS};
+use crate::util::memory::LAYOUTS;
+use crate::util::parser::split_word;
+use crate::util::{layout, Commandable, Message};
+use once_cell::sync::Lazy;
+
+static USAGE: Lazy<String> = Lazy::new(|| format!("edit-board <layout> <{}>", BOARDS.join("|")));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let mut arg = msg.arg;
+        let name = split_word(&mut arg).to_lowercase();
+        let board = arg.trim().to_lowercase();
+        if name.is_empty() || board.is_empty() {
+            return self.help();
+        }
+        if !BOARDS.contains(&board.as_str()) {
+            return format!("Error: unknown board `{board}`, expected one of: {}", BOARDS.join(", "));
+        }
+
+        {
+            let mut ll = LAYOUTS.get_mut(&name);
+            let Some(ll) = ll.checked() else {
+                return format!("Error: `{name}` does not exist");
+            };
+            if ll.user != msg.id && !ADMINS.contains(&msg.id) {
+                return format!("Error: you don't own `{name}`");
+            }
+            if ll.board == board {
+                return format!("`{name}` already uses board `{board}`");
+            }
+            ll.push_revision(msg.id);
+            layout::set_board(ll, &board);
+        }
+        format!("`{name}` now uses board `{board}`\n{}", layout::to_string(&LAYOUTS.get(&name), msg.id))
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        &USAGE
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "change the board of one of your layouts"
+    }
+
+    fn records_author(&self) -> bool {
+        true
+    }
+}
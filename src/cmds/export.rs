@@ -0,0 +1,73 @@
+use crate::util::core::Reply;
+use crate::util::export::{export, EXPORT_FORMATS};
+use crate::util::memory::LAYOUTS;
+use crate::util::parser::{get_kwargs, KwargType};
+use crate::util::{Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("format".to_owned(), KwargType::Str),
+]));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        self.exec_reply(msg).content
+    }
+
+    fn exec_reply(&self, msg: &Message) -> Reply {
+        let kwargs = match get_kwargs(msg.arg, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return Reply::from(err.to_string()),
+        };
+        let name = &kwargs.arg;
+        let Some(format) = kwargs["format"].unwrap_str().map(str::to_lowercase) else {
+            return Reply::from(self.help());
+        };
+        if name.is_empty() {
+            return Reply::from(self.help());
+        }
+
+        let current = match LAYOUTS.find(name) {
+            Ok(current) => current,
+            Err(err) => return Reply::from(err),
+        };
+        let (attachment, skipped) = match export(&current, &format) {
+            Ok(exported) => exported,
+            Err(err) => return Reply::from(err),
+        };
+        let mut content = format!("{}Exported `{}` to {format}. {}\n", current.note(name), current.name, get_install_hint(&format));
+        if !skipped.is_empty() {
+            let skipped: Vec<String> = skipped.iter().map(|key| format!("`{key}`")).collect();
+            content.push_str(&format!("Left out keys without a place on the keyboard: {}\n", skipped.join(" ")));
+        }
+        Reply {
+            content,
+            files: vec![attachment],
+        }
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "export <layout> --format <xkb|klc|keylayout|qmk|kanata|kmonad|ahk>"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "get a file to install a layout on your OS or keyboard firmware"
+    }
+}
+
+fn get_install_hint(format: &str) -> &'static str {
+    debug_assert!(EXPORT_FORMATS.contains(&format));
+    match format {
+        "xkb" => "Copy it to `/usr/share/X11/xkb/symbols/` and select it with `setxkbmap`.",
+        "klc" => "Open it in Microsoft Keyboard Layout Creator and build the setup package.",
+        "keylayout" => "Copy it to `~/Library/Keyboard Layouts/` and add it in the input sources.",
+        "qmk" => "Use it as the `keymap.c` of a split 3x5+3 keyboard.",
+        "kanata" => "Run it with `kanata --cfg <file>`.",
+        "kmonad" => "Set the device file of your keyboard, then run it with `kmonad <file>`.",
+        _ => "Run it with AutoHotkey v2.",
+    }
+}
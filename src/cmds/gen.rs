@@ -0,0 +1,158 @@
+use crate::util::analyzer::NAMED_STATS;
+use crate::util::consts::STANDARD_KEYS;
+use crate::util::core::{Key, Layout, LayoutConfig};
+use crate::util::corpora::{corpus_ngrams, get_user_corpus, CORPORA};
+use crate::util::incremental::{CompiledCorpus, IncrementalAnalyzer};
+use crate::util::layout::{apply_fingermap, get_fingermap};
+use crate::util::optimizer::{anneal, Budget, IncrementalScorer};
+use crate::util::parser::{get_kwargs, KwargType};
+use crate::util::score::SCORE_MODELS;
+use crate::util::{analyzer, layout, Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+use std::time::Duration;
+
+const DEFAULT_SECS: f64 = 5.0;
+const MAX_SECS: f64 = 60.0;
+const DEFAULT_TEMPLATE: &str = "~~~~~~~~~~\n~~~~~~~~~~\n~~~~~~~~~~";
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("board".to_owned(), KwargType::Str),
+    ("corpus".to_owned(), KwargType::Str),
+    ("fingers".to_owned(), KwargType::Str),
+    ("iterations".to_owned(), KwargType::Str),
+    ("keys".to_owned(), KwargType::Str),
+    ("preset".to_owned(), KwargType::Str),
+    ("time".to_owned(), KwargType::Str),
+    ("weights".to_owned(), KwargType::Vec),
+]));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let parts: Vec<&str> = msg.arg.split("```").collect();
+        let template = parts.get(1).unwrap_or(&"").trim_end().to_lowercase();
+        let template = if template.trim().is_empty() { DEFAULT_TEMPLATE } else { &template };
+
+        let kwargs = match get_kwargs(parts[0], &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return err.to_string(),
+        };
+        if !kwargs.arg.is_empty() {
+            return self.help();
+        }
+
+        let corpus = match kwargs["corpus"].unwrap_str() {
+            None => get_user_corpus(msg.id),
+            Some(corpus) => corpus.to_lowercase(),
+        };
+        if !CORPORA.contains(&corpus) {
+            return format!("The corpus `{corpus}` doesn\'t exist.");
+        }
+        let (preset, model) = match SCORE_MODELS.get(kwargs["preset"].unwrap_str()) {
+            Ok(model) => model,
+            Err(err) => return err,
+        };
+        let mut model = model.clone();
+        for weight in kwargs["weights"].unwrap_vec().unwrap_or_default() {
+            let Some((name, value)) = weight.split_once('=') else {
+                return format!("Error: expected weights as `stat=weight`, got `{weight}`");
+            };
+            if !NAMED_STATS.iter().any(|(stat, _)| *stat == name) {
+                return format!("Error: unknown stat `{name}`");
+            }
+            match value.parse::<f64>() {
+                Ok(value) => { model.metrics.insert(name.to_owned(), value); }
+                Err(_) => return format!("Error: `{value}` is not a valid weight"),
+            }
+        }
+
+        let board = kwargs["board"].unwrap_str().unwrap_or("ortho").to_lowercase();
+        if !matches!(board.as_str(), "ortho" | "stagger" | "angle") {
+            return format!("Error: cannot generate for board `{board}`, expected ortho, stagger or angle");
+        }
+        let secs = match kwargs["time"].unwrap_str().map(str::parse::<f64>) {
+            None => DEFAULT_SECS,
+            Some(Ok(secs)) if secs > 0.0 => secs.min(MAX_SECS),
+            Some(_) => return "Error: time must be a positive number of seconds".to_owned(),
+        };
+        let iterations = match kwargs["iterations"].unwrap_str().map(str::parse::<u64>) {
+            None => u64::MAX,
+            Some(Ok(iterations)) if iterations > 0 => iterations,
+            Some(_) => return "Error: iterations must be a positive integer".to_owned(),
+        };
+        let keys = kwargs["keys"].unwrap_str().unwrap_or(STANDARD_KEYS).to_lowercase();
+
+        let (mut layout, free) = match fill_template(template, &board, &keys) {
+            Ok(filled) => filled,
+            Err(err) => return err,
+        };
+        match get_fingermap(kwargs["fingers"].unwrap_str(), parts.get(3).copied()) {
+            Ok(Some(fingermap)) => if let Err(err) = apply_fingermap(&mut layout, &fingermap) {
+                return err;
+            },
+            Ok(None) => (),
+            Err(err) => return err,
+        }
+        let monograms = corpus_ngrams::<1>(&corpus);
+        let trigrams = corpus_ngrams::<3>(&corpus);
+        let compiled = CompiledCorpus::new(&trigrams);
+        let mut scorer = IncrementalScorer::new(&model, &compiled, &layout, &monograms);
+        let free: Vec<usize> = free.iter()
+            .filter_map(|key| scorer.analyzer().index(*key))
+            .collect();
+        let result = anneal(&mut scorer, &free, Budget {
+            iterations,
+            time: Duration::from_secs_f64(secs),
+        });
+
+        let ll = LayoutConfig::new("generated".to_owned(), msg.id, board, result.layout);
+        let stats = IncrementalAnalyzer::new(&compiled, &ll.keys).stats();
+        let finger_usage = analyzer::fingers_usage(&ll, &monograms);
+        let stats_str = layout::get_stats_str(&stats, &finger_usage);
+        let input_str = layout::get_input_str(&ll);
+        let corpus_name = corpus.to_uppercase();
+
+        format!("Generated in {} iterations ({:.1}s)\n\
+                 ```\n{input_str}\n```\n\
+                 ```\n\
+                 {corpus_name}:\n\
+                 {stats_str}\
+                 \n\
+                 Score: {:.2} ({preset})\n\
+                 ```\n\
+                 Save it with `add <layout>` followed by the matrix above",
+                result.iterations, result.elapsed.as_secs_f64(), result.score)
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "gen [--board <board>] [--corpus <corpus>] [--preset <score preset>] [--weights <stat=weight>...]\n    \
+         [--keys <keys>] [--fingers <standard|angle>] [--time <seconds>] [--iterations <count>]\n    \
+         [``\u{200b}`template`\u{200b}`` [``\u{200b}`fingermap`\u{200b}``]]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "generate a layout, filling the ~ of the template while keeping its other keys in place"
+    }
+}
+
+/// Places the pinned keys of the template, and fills its free slots with the remaining keys.
+/// Returns the layout and its free keys.
+fn fill_template(template: &str, board: &str, keys: &str) -> Result<(Layout, Vec<Key>), String> {
+    let (_, mut layout, slots) = layout::parse_template(template, Some(board))?;
+
+    let mut free: Vec<Key> = Vec::new();
+    for key in keys.chars().filter(|c| !c.is_whitespace()) {
+        if !layout.contains_key(&key) && !free.contains(&key) {
+            free.push(key);
+        }
+    }
+    if free.len() != slots.len() {
+        return Err(format!("Error: the template has {} free slots, but there are {} keys to place",
+                           slots.len(), free.len()));
+    }
+    layout.extend(free.iter().copied().zip(slots));
+    Ok((layout, free))
+}
@@ -0,0 +1,91 @@
+use crate::util::authors::AUTHORS;
+use crate::util::core::LayoutConfig;
+use crate::util::memory::LAYOUTS;
+use crate::util::parser::split_word;
+use crate::util::{layout, Commandable, Message};
+
+/// Revisions listed at once, so that the reply fits in a message
+const MAX_REVISIONS: usize = 15;
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let mut arg = msg.arg;
+        let name = split_word(&mut arg).to_lowercase();
+        if name.is_empty() {
+            return self.help();
+        }
+        let current = match LAYOUTS.find(&name) {
+            Ok(current) => current,
+            Err(err) => return err,
+        };
+        let revisions: Vec<LayoutConfig> = (1..=current.history.len() + 1)
+            .filter_map(|rev| current.revision(rev))
+            .collect();
+
+        let note = current.note(&name);
+        if arg.is_empty() || current.history.is_empty() {
+            return format!("{note}{}", get_history_str(&current, &revisions));
+        }
+        let reply = match arg.trim().parse::<usize>() {
+            Ok(rev) if (1..revisions.len()).contains(&rev) => {
+                let (before, after) = (&revisions[rev - 1], &revisions[rev]);
+                format!("Changes from `{}` to `{}`:\n```\n{}\n```",
+                        before.title(), after.title(), layout::get_moved_str(&before.keys, after))
+            }
+            _ => format!("Error: `{}` has changes from revisions 1 to {}", current.name, revisions.len() - 1),
+        };
+        format!("{note}{reply}")
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "history <layout> [<revision>]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "see the past revisions of a layout, or the keys that moved after a revision"
+    }
+}
+
+fn get_history_str(current: &LayoutConfig, revisions: &[LayoutConfig]) -> String {
+    if current.history.is_empty() {
+        return format!("`{}` has not been changed since it was added", current.name);
+    }
+    let authors = AUTHORS.read().unwrap();
+    let get_name = |id: u64| authors.get_name(id).map_or_else(|| id.to_string(), str::to_owned);
+
+    let mut lines: Vec<String> = Vec::new();
+    for (index, revision) in current.history.iter().enumerate() {
+        let (before, after) = (&revisions[index], &revisions[index + 1]);
+        let mut changes: Vec<String> = Vec::new();
+        let new_name = current.history.get(index + 1).map_or(&current.name, |revision| &revision.name);
+        if revision.name != *new_name {
+            changes.push(format!("renamed from `{}` to `{new_name}`", revision.name));
+        }
+        if before.user != after.user {
+            changes.push(format!("assigned from {} to {}", get_name(before.user), get_name(after.user)));
+        }
+        if before.board != after.board {
+            changes.push(format!("board {} to {}", before.board, after.board));
+        }
+        let moved = layout::get_moved_keys(&before.keys, &after.keys);
+        if !moved.is_empty() {
+            let moved: String = moved.iter().map(|key| format!(" {key}")).collect();
+            changes.push(format!("moved{moved}"));
+        }
+        if changes.is_empty() {
+            changes.push("fingers changed".to_owned());
+        }
+        lines.push(format!("**{}** <t:{}:f> by {}: {}",
+                           index + 1, revision.time, get_name(revision.editor), changes.join(", ")));
+    }
+    let skipped = lines.len().saturating_sub(MAX_REVISIONS);
+    let mut s = format!("History of `{}`, see a revision with `view {}@<revision>`\n", current.name, current.name);
+    if skipped > 0 {
+        s.push_str(&format!("...{skipped} older revisions\n"));
+    }
+    s.push_str(&lines[skipped..].join("\n"));
+    s.push_str(&format!("\n**{}** is the current revision", revisions.len()));
+    s
+}
@@ -0,0 +1,77 @@
+use crate::util::import::{decode, get_name, import};
+use crate::util::memory::LAYOUTS;
+use crate::util::parser::{get_kwargs, KwargType};
+use crate::util::{layout, Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+
+/// Languages that may follow the opening backticks of a pasted layout
+const CODE_LANGUAGES: [&str; 6] = ["json", "txt", "text", "klc", "genkey", "kb"];
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("board".to_owned(), KwargType::Str),
+]));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let mut parts = msg.arg.split("```");
+        let kwarg_str = parts.next().unwrap_or("");
+        let kwargs = match get_kwargs(kwarg_str, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return err.to_string(),
+        };
+
+        let (filename, content) = match (msg.files.first(), parts.next()) {
+            (Some(file), _) => match decode(&file.data) {
+                Ok(content) => (Some(file.filename.as_str()), content),
+                Err(err) => return err,
+            },
+            (None, Some(block)) => (None, strip_language(block).to_owned()),
+            (None, None) => return self.help(),
+        };
+        let board = kwargs["board"].unwrap_str().map(str::to_lowercase);
+        let imported = match import(filename, &content, board.as_deref()) {
+            Ok(imported) => imported,
+            Err(err) => return err,
+        };
+
+        let name = match kwargs.arg.is_empty() {
+            false => kwargs.arg.to_lowercase(),
+            true => match get_name(&imported, filename) {
+                Some(name) => name,
+                None => return "Error: the layout has no name, please give it one".to_owned(),
+            },
+        };
+        let format = imported.format.name();
+        match layout::create(&name, msg.id, imported.board, imported.keys) {
+            Ok(()) => format!("Imported `{name}` from {format}\n{}", layout::to_string(&LAYOUTS.get(&name), msg.id)),
+            Err(err) => err,
+        }
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "import [name] [--board <board>] (with an attachment, or ``\u{200b}`layout`\u{200b}``)"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "add a layout from a keyboard-layout-editor, Oxeylyzer, Genkey, .klc or cmini JSON file"
+    }
+
+    fn reads_attachments(&self) -> bool {
+        true
+    }
+
+    fn records_author(&self) -> bool {
+        true
+    }
+}
+
+fn strip_language(block: &str) -> &str {
+    match block.split_once('\n') {
+        Some((language, rest)) if CODE_LANGUAGES.contains(&language.trim()) => rest,
+        _ => block,
+    }
+}
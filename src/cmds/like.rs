@@ -1,3 +1,4 @@
+use crate::util::core::Like;
 use crate::util::{Commandable, Message};
 use crate::util::memory::{LAYOUTS, LIKES};
 
@@ -9,16 +10,20 @@ impl Commandable for Command {
         if name.is_empty() {
             return self.help();
         }
-        let ll = &*LAYOUTS.find(name);
-        if ll.name == "qwerty" {
+        // The layout is released before taking the likes, so that a waiting writer can't deadlock them
+        let (name, key) = match LAYOUTS.find_confident(name) {
+            Ok(ll) => (ll.name.clone(), ll.store_key()),
+            Err(err) => return err,
+        };
+        if name == "qwerty" {
             return "You can't like Qwerty :yellow_circle:".to_owned();
         }
         let has_liked = {
             // Must drop or else deadlock
             let likes = LIKES.read().unwrap();
-            match likes.get(&ll.name) {
+            match likes.get(&key) {
                 None => false,
-                Some(liked_users) => liked_users.contains(&msg.id),
+                Some(likes) => likes.iter().any(|like| like.user == msg.id),
             }
         };
         if has_liked {
@@ -26,18 +31,18 @@ impl Commandable for Command {
         }
         {
             let mut likes = LIKES.write().unwrap();
-            let like_count = match likes.get_mut(&ll.name) {
+            let like_count = match likes.get_mut(&key) {
                 None => {
-                    likes.insert(ll.name.clone(), vec![msg.id]);
+                    likes.insert(key, vec![Like::new(msg.id)]);
                     1usize
                 }
                 Some(liked_users) => {
-                    liked_users.push(msg.id);
+                    liked_users.push(Like::new(msg.id));
                     liked_users.len()
                 }
             };
             let s = if like_count == 1 { "" } else { "s" };
-            format!("You liked {}. (Now at {like_count} like{s})", ll.name)
+            format!("You liked {name}. (Now at {like_count} like{s})")
         }
     }
 
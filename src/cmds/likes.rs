@@ -1,5 +1,5 @@
 use crate::util::{Commandable, Message};
-use crate::util::memory::LIKES;
+use crate::util::memory::{LAYOUTS, LIKES};
 
 pub struct Command;
 
@@ -8,12 +8,13 @@ impl Commandable for Command {
         let id = msg.id;
         let name = &msg.author.name;
         let mut response = format!("```\n{name}'s liked layouts:\n");
+        let layouts = LAYOUTS.read().unwrap();
         let likes = LIKES.read().unwrap();
 
         let mut liked_layouts = Vec::<&str>::new();
-        for (layout, liked_users) in likes.iter() {
-            if liked_users.contains(&id) {
-                liked_layouts.push(layout);
+        for ll in layouts.values() {
+            if likes.get(&ll.store_key()).is_some_and(|likes| likes.iter().any(|like| like.user == id)) {
+                liked_layouts.push(&ll.name);
             }
         }
         liked_layouts.sort();
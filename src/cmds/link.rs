@@ -0,0 +1,59 @@
+use crate::util::consts::ADMINS;
+use crate::util::core::LayoutId;
+use crate::util::links::{self, MAX_LINKS};
+use crate::util::memory::LAYOUTS;
+use crate::util::parser::split_word;
+use crate::util::{Commandable, Message};
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let mut arg = msg.arg;
+        let name = split_word(&mut arg).to_lowercase();
+        let url = arg.trim().trim_start_matches('<').trim_end_matches('>');
+        if name.is_empty() {
+            return self.help();
+        }
+        if url.is_empty() {
+            let (id, note, name) = match LAYOUTS.find(&name) {
+                Ok(ll) => (ll.id, ll.note(&name), ll.name.clone()),
+                Err(err) => return err,
+            };
+            return match links::get_link(id) {
+                link if link.is_empty() => format!("{note}`{name}` has no links"),
+                link => format!("{note}Links of `{name}`:\n{link}"),
+            };
+        }
+        let id = match check_owner(&name, msg.id) {
+            Ok(id) => id,
+            Err(err) => return err,
+        };
+        match links::add_link(id, &name, url) {
+            Ok(count) => format!("Linked `{name}` to <{url}> ({count} of {MAX_LINKS} links)"),
+            Err(err) => err,
+        }
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "link <layout> [<url>]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "see the links of a layout, or add a link like its repo or blog post to one of your layouts"
+    }
+
+    fn records_author(&self) -> bool {
+        true
+    }
+}
+
+/// Only the owner of a layout and the mods can change its links. Returns the id of the layout.
+pub fn check_owner(name: &str, id: u64) -> Result<LayoutId, String> {
+    let ll = LAYOUTS.get(name);
+    match ll.checked() {
+        None => Err(format!("Error: `{name}` does not exist")),
+        Some(ll) if ll.user != id && !ADMINS.contains(&id) => Err(format!("Error: you don't own `{name}`")),
+        Some(ll) => Ok(ll.id),
+    }
+}
@@ -0,0 +1,152 @@
+use crate::util::authors::AUTHORS;
+use crate::util::consts::{BOARDS, MESSAGE_LIMIT};
+use crate::util::core::LayoutConfig;
+use crate::util::memory::{LAYOUTS, LIKES};
+use crate::util::parser::{get_kwargs, parse_date, KwargType};
+use crate::util::{Commandable, Message};
+use fxhash::FxHashMap;
+use glob::Pattern;
+use once_cell::sync::Lazy;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("by".to_owned(), KwargType::Str),
+    ("board".to_owned(), KwargType::Str),
+    ("tag".to_owned(), KwargType::Str),
+    ("liked".to_owned(), KwargType::Bool),
+    ("since".to_owned(), KwargType::Str),
+    ("until".to_owned(), KwargType::Str),
+    ("page".to_owned(), KwargType::Str),
+]));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let kwargs = match get_kwargs(msg.arg, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return err.to_string(),
+        };
+        let pattern = match kwargs.arg.to_lowercase() {
+            pattern if pattern.is_empty() => None,
+            pattern => match Pattern::new(&pattern) {
+                Ok(pattern) => Some(pattern),
+                Err(err) => return format!("Error: `{pattern}` is not a valid name pattern, {err}"),
+            },
+        };
+        let (user, author) = match kwargs["by"].unwrap_str() {
+            None => (None, None),
+            Some(author) => {
+                let authors = AUTHORS.read().unwrap();
+                let id = match authors.resolve(author) {
+                    Ok(id) => id,
+                    Err(err) => return err,
+                };
+                (Some(id), authors.get_name(id).map(str::to_owned))
+            }
+        };
+        let board = kwargs["board"].unwrap_str().map(str::to_lowercase);
+        if let Some(board) = board.as_deref().filter(|board| !BOARDS.contains(board)) {
+            return format!("Error: unknown board `{board}`, expected one of: {}", BOARDS.join(", "));
+        }
+        let tag = kwargs["tag"].unwrap_str().map(str::to_lowercase);
+        let since = match kwargs["since"].unwrap_str().map(parse_date).transpose() {
+            Ok(since) => since,
+            Err(err) => return err,
+        };
+        // The whole day is included
+        let until = match kwargs["until"].unwrap_str().map(parse_date).transpose() {
+            Ok(until) => until.map(|until| until + 86400),
+            Err(err) => return err,
+        };
+        let page = match kwargs["page"].unwrap_str().map(str::parse::<usize>) {
+            None => 1,
+            Some(Ok(page)) if page > 0 => page,
+            Some(_) => return "Error: `--page` must be a positive number".to_owned(),
+        };
+
+        let layouts = LAYOUTS.read().unwrap();
+        let likes = LIKES.read().unwrap();
+        let liked = |ll: &LayoutConfig| likes.get(&ll.store_key()).is_some_and(|likes| likes.iter().any(|like| like.user == msg.id));
+        let mut matches: Vec<&LayoutConfig> = layouts.values()
+            .filter(|ll| pattern.as_ref().is_none_or(|pattern| pattern.matches(&ll.name)))
+            .filter(|ll| user.is_none_or(|user| ll.user == user))
+            .filter(|ll| board.as_ref().is_none_or(|board| ll.board == *board))
+            .filter(|ll| tag.as_ref().is_none_or(|tag| ll.meta.tags.contains(tag)))
+            .filter(|ll| !kwargs["liked"].unwrap_bool() || liked(ll))
+            .filter(|ll| since.is_none_or(|since| ll.meta.created.is_some_and(|created| created >= since)))
+            .filter(|ll| until.is_none_or(|until| ll.meta.created.is_some_and(|created| created < until)))
+            .collect();
+        matches.sort_by(|ll0, ll1| ll0.name.cmp(&ll1.name));
+
+        if matches.is_empty() {
+            return "No layouts match, try fewer filters".to_owned();
+        }
+        let mut title = format!("{} layouts", matches.len());
+        if let Some(author) = author {
+            title.push_str(&format!(" by {author}"));
+        }
+        let authors = AUTHORS.read().unwrap();
+        let lines: Vec<String> = matches.iter().map(|ll| {
+            let author = authors.get_name(ll.user).unwrap_or("Unknown");
+            let like_count = likes.get(&ll.store_key()).map_or(0, Vec::len);
+            format!("{:<24} {author:<16} {like_count:>3}", ll.name)
+        }).collect();
+        let pages = paginate(&lines, MESSAGE_LIMIT - 200);
+        let Some(lines) = pages.get(page - 1) else {
+            return format!("Error: there are only {} pages", pages.len());
+        };
+        let mut s = format!("```\n{title} (page {page} of {}):\n{:<24} {:<16} {:>3}\n", pages.len(), "name", "author", "likes");
+        s.push_str(&lines.join("\n"));
+        s.push_str("\n```");
+        if page < pages.len() {
+            s.push_str(&format!("\nSee the next page with `--page {}`", page + 1));
+        }
+        s
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "list [<name pattern>] [--by <author>] [--board <board>] [--tag <tag>] [--liked] \
+         [--since <YYYY-MM-DD>] [--until <YYYY-MM-DD>] [--page <n>]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "browse the layouts, where the name pattern may use `*` like `semi*`"
+    }
+}
+
+/// Splits lines into pages of at most `max_len` characters, a page having at least one line
+pub fn paginate(lines: &[String], max_len: usize) -> Vec<&[String]> {
+    let mut pages: Vec<&[String]> = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+    for (index, line) in lines.iter().enumerate() {
+        if index > start && len + line.len() + 1 > max_len {
+            pages.push(&lines[start..index]);
+            start = index;
+            len = 0;
+        }
+        len += line.len() + 1;
+    }
+    if start < lines.len() {
+        pages.push(&lines[start..]);
+    }
+    pages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate() {
+        let lines: Vec<String> = ["aaaa", "bbbb", "cccc", "dddddddddddd", "e"].map(str::to_owned).to_vec();
+        // Each line takes its length and a line break
+        let pages = paginate(&lines, 10);
+        assert_eq!(pages, vec![&lines[0..2], &lines[2..3], &lines[3..4], &lines[4..5]]);
+        assert_eq!(paginate(&lines, 100), vec![&lines[..]]);
+        assert!(paginate(&[], 10).is_empty());
+        // A line longer than a page still gets its own page
+        assert_eq!(paginate(&lines[3..], 5), vec![&lines[3..4], &lines[4..5]]);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::util::consts::ADMINS;
+use crate::util::layout::{check_tag, get_meta_str};
+use crate::util::memory::LAYOUTS;
+use crate::util::parser::{get_kwargs, KwargType};
+use crate::util::{Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+
+const MAX_DESCRIPTION_LEN: usize = 300;
+const MAX_TAGS: usize = 8;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("desc".to_owned(), KwargType::Str),
+    ("tags".to_owned(), KwargType::Vec),
+    ("lang".to_owned(), KwargType::Str),
+]));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let kwargs = match get_kwargs(msg.arg, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return err.to_string(),
+        };
+        let name = kwargs.arg.to_lowercase();
+        if name.is_empty() {
+            return self.help();
+        }
+        let description = kwargs["desc"].unwrap_str();
+        let tags = kwargs["tags"].unwrap_vec();
+        let language = kwargs["lang"].unwrap_str();
+
+        if description.is_none() && tags.is_none() && language.is_none() {
+            let ll = match LAYOUTS.find(&name) {
+                Ok(ll) => ll,
+                Err(err) => return err,
+            };
+            return match get_meta_str(&ll.meta) {
+                meta if meta.is_empty() => format!("{}`{}` has no description, tags or language", ll.note(&name), ll.name),
+                meta => format!("{}`{}`\n{meta}", ll.note(&name), ll.name),
+            };
+        }
+        if description.is_some_and(|description| description.chars().count() > MAX_DESCRIPTION_LEN) {
+            return format!("Error: descriptions can be at most {MAX_DESCRIPTION_LEN} characters long");
+        }
+        let tags: Option<Vec<String>> = tags.map(|tags| {
+            let mut tags: Vec<String> = tags.iter().map(|tag| tag.trim_matches(',').to_lowercase()).collect();
+            tags.sort();
+            tags.dedup();
+            tags
+        });
+        if let Some(tags) = &tags {
+            if tags.len() > MAX_TAGS {
+                return format!("Error: layouts can have at most {MAX_TAGS} tags");
+            }
+            if let Some(err) = tags.iter().find_map(|tag| check_tag(tag).err()) {
+                return err;
+            }
+        }
+
+        let mut ll = LAYOUTS.get_mut(&name);
+        let Some(ll) = ll.checked() else {
+            return format!("Error: `{name}` does not exist");
+        };
+        if ll.user != msg.id && !ADMINS.contains(&msg.id) {
+            return format!("Error: you don't own `{name}`");
+        }
+        if let Some(description) = description {
+            ll.meta.description = description.to_owned();
+        }
+        if let Some(tags) = tags {
+            ll.meta.tags = tags;
+        }
+        if let Some(language) = language {
+            ll.meta.language = (!language.is_empty()).then(|| language.to_lowercase());
+        }
+        format!("Updated `{name}`\n{}", get_meta_str(&ll.meta))
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "meta <layout> [--desc <description>] [--tags <tag>...] [--lang <language>]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "see or change the description, tags and language of a layout, where an empty value clears it"
+    }
+
+    fn records_author(&self) -> bool {
+        true
+    }
+}
@@ -8,10 +8,26 @@ mod corpus;
 mod add;
 mod remove;
 mod assign;
+mod author;
 mod rename;
 mod like;
 mod likes;
+mod list;
+mod popular;
+mod link;
 mod unlike;
+mod unlink;
+mod sort;
+mod gen;
+mod swap;
+mod import;
+mod export;
+mod history;
+mod check_data;
+mod meta;
+mod name;
+mod edit;
+mod edit_board;
 
 use fxhash::FxHashMap;
 use once_cell::sync::Lazy;
@@ -22,16 +38,32 @@ pub static COMMANDS: Lazy<FxHashMap<String, DynCommand>> = Lazy::new(|| {
         ("8ball", _8ball::Command.init()),
         ("add", add::Command.init()),
         ("assign", assign::Command.init()),
+        ("author", author::Command.init()),
+        ("check-data", check_data::Command.init()),
         ("corpus", corpus::Command.init()),
+        ("edit", edit::Command.init()),
+        ("edit-board", edit_board::Command.init()),
+        ("export", export::Command.init()),
+        ("gen", gen::Command.init()),
         ("gh", github::Command.init()),
         ("github", github::Command.init()),
         ("help", help::Command.init()),
+        ("history", history::Command.init()),
+        ("import", import::Command.init()),
         ("like", like::Command.init()),
         ("likes", likes::Command.init()),
+        ("link", link::Command.init()),
+        ("list", list::Command.init()),
+        ("meta", meta::Command.init()),
+        ("name", name::Command.init()),
+        ("popular", popular::Command.init()),
         ("remove", remove::Command.init()),
         ("rename", rename::Command.init()),
+        ("sort", sort::Command.init()),
         ("suggest", suggest::Command.init()),
+        ("swap", swap::Command.init()),
         ("unlike", unlike::Command.init()),
+        ("unlink", unlink::Command.init()),
         ("view", view::Command.init()),
     ].into_iter().map(|(name, obj)| (name.to_string(), obj)))
 });
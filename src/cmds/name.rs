@@ -0,0 +1,42 @@
+use crate::util::authors::AUTHORS;
+use crate::util::{Commandable, Message};
+
+const MAX_NAME_LEN: usize = 32;
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let name = msg.arg.trim();
+        let mut authors = AUTHORS.write().unwrap();
+        if name.is_empty() {
+            return match authors.get_names(msg.id) {
+                None => "You don't have a name yet, set one with `name <display name>`".to_owned(),
+                Some(names) if names.len() == 1 => format!("Your name is {}", names[0]),
+                Some(names) => format!("Your name is {}, also known as {}", names[0], names[1..].join(", ")),
+            };
+        }
+        if name.chars().count() > MAX_NAME_LEN {
+            return format!("Error: names can be at most {MAX_NAME_LEN} characters long");
+        }
+        if name.chars().any(|c| matches!(c, '`' | '@' | '<' | '>' | '\n')) {
+            return "Error: names cannot contain `` ` ``, `@`, `<`, `>` or line breaks".to_owned();
+        }
+        // A name made of digits would be read as an ID
+        if name.chars().all(|c| c.is_ascii_digit()) {
+            return "Error: names cannot be only digits".to_owned();
+        }
+        match authors.set_name(msg.id, name.to_owned()) {
+            Ok(()) => format!("Your layouts now show {name} as their author"),
+            Err(err) => err,
+        }
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "name [<display name>]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "see your names, or choose the name shown as the author of your layouts"
+    }
+}
@@ -0,0 +1,157 @@
+use crate::cmds::list::paginate;
+use crate::util::authors::AUTHORS;
+use crate::util::consts::{BOARDS, MESSAGE_LIMIT};
+use crate::util::core::{unix_time, Like, LayoutConfig};
+use crate::util::memory::{LAYOUTS, LIKES};
+use crate::util::parser::{get_kwargs, KwargType};
+use crate::util::{Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+
+const TOP_LAYOUTS: usize = 25;
+const TOP_LAYOUTS_PER_BOARD: usize = 5;
+const DEFAULT_TRENDING_DAYS: u64 = 30;
+const MAX_TRENDING_DAYS: u64 = 3650;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("board".to_owned(), KwargType::Str),
+    ("boards".to_owned(), KwargType::Bool),
+    ("trending".to_owned(), KwargType::Str),
+    ("page".to_owned(), KwargType::Str),
+]));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let kwargs = match get_kwargs(msg.arg, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return err.to_string(),
+        };
+        if !kwargs.arg.is_empty() {
+            return self.help();
+        }
+        let board = kwargs["board"].unwrap_str().map(str::to_lowercase);
+        if let Some(board) = board.as_deref().filter(|board| !BOARDS.contains(board)) {
+            return format!("Error: unknown board `{board}`, expected one of: {}", BOARDS.join(", "));
+        }
+        if board.is_some() && kwargs["boards"].unwrap_bool() {
+            return "Error: use either `--board` or `--boards`".to_owned();
+        }
+        let days = match kwargs["trending"].unwrap_str() {
+            None => None,
+            Some("") => Some(DEFAULT_TRENDING_DAYS),
+            Some(days) => match days.parse::<u64>() {
+                Ok(days) if (1..=MAX_TRENDING_DAYS).contains(&days) => Some(days),
+                _ => return format!("Error: `--trending` takes a number of days from 1 to {MAX_TRENDING_DAYS}"),
+            },
+        };
+        let page = match kwargs["page"].unwrap_str().map(str::parse::<usize>) {
+            None => 1,
+            Some(Ok(page)) if page > 0 => page,
+            Some(_) => return "Error: `--page` must be a positive number".to_owned(),
+        };
+        let since = days.map(|days| unix_time().saturating_sub(days * 86400));
+
+        let layouts = LAYOUTS.read().unwrap();
+        let likes = LIKES.read().unwrap();
+        let mut ranked: Vec<(&LayoutConfig, usize)> = layouts.values()
+            .filter(|ll| board.as_ref().is_none_or(|board| ll.board == *board))
+            .map(|ll| (ll, likes.get(&ll.store_key()).map_or(0, |likes| count_likes(likes, since))))
+            .filter(|(_, like_count)| *like_count > 0)
+            .collect();
+        ranked.sort_by(|(ll0, count0), (ll1, count1)| count1.cmp(count0).then_with(|| ll0.name.cmp(&ll1.name)));
+
+        let mut title = match days {
+            None => "Most liked layouts".to_owned(),
+            Some(days) => format!("Most liked layouts of the last {days} days"),
+        };
+        if let Some(board) = &board {
+            title.push_str(&format!(" on {board}"));
+        }
+        if kwargs["boards"].unwrap_bool() {
+            title.push_str(" by board");
+        }
+        let note = match days {
+            None => "",
+            Some(_) => "\nOnly likes given since likes are timed count towards trending",
+        };
+        if ranked.is_empty() {
+            return format!("{title}: no layouts have been liked{note}");
+        }
+
+        let authors = AUTHORS.read().unwrap();
+        let line = |rank: usize, ll: &LayoutConfig, like_count: usize| {
+            let author = authors.get_name(ll.user).unwrap_or("Unknown");
+            format!("{rank:>2}. {:<24} {author:<16} {like_count:>3}", ll.name)
+        };
+        let lines: Vec<String> = if kwargs["boards"].unwrap_bool() {
+            BOARDS.iter().flat_map(|board| {
+                let board_lines = ranked.iter()
+                    .filter(|(ll, _)| ll.board == *board)
+                    .take(TOP_LAYOUTS_PER_BOARD)
+                    .enumerate()
+                    .map(|(index, (ll, like_count))| line(index + 1, ll, *like_count))
+                    .collect::<Vec<String>>();
+                match board_lines.is_empty() {
+                    true => vec![format!("{board}: no likes")],
+                    false => std::iter::once(format!("{board}:")).chain(board_lines).collect(),
+                }
+            }).collect()
+        } else {
+            ranked.iter()
+                .take(TOP_LAYOUTS)
+                .enumerate()
+                .map(|(index, (ll, like_count))| line(index + 1, ll, *like_count))
+                .collect()
+        };
+        let pages = paginate(&lines, MESSAGE_LIMIT - 200);
+        let Some(lines) = pages.get(page - 1) else {
+            return format!("Error: there are only {} pages", pages.len());
+        };
+        if pages.len() > 1 {
+            title.push_str(&format!(" (page {page} of {})", pages.len()));
+        }
+        let mut s = format!("```\n{title}:\n{}\n```{note}", lines.join("\n"));
+        if page < pages.len() {
+            s.push_str(&format!("\nSee the next page with `--page {}`", page + 1));
+        }
+        s
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "popular [--board <board>] [--boards] [--trending [<days>]] [--page <n>]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "see the most liked layouts, overall, on a board or in the last days (30 by default)"
+    }
+}
+
+/// Counts the likes given since a time, or all of them, untimed likes only counting towards all of them
+fn count_likes(likes: &[Like], since: Option<u64>) -> usize {
+    likes.iter()
+        .filter(|like| since.is_none_or(|since| like.time.is_some_and(|time| time >= since)))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_likes() {
+        let likes = [
+            Like { user: 1, time: None },
+            Like { user: 2, time: Some(100) },
+            Like { user: 3, time: Some(200) },
+        ];
+        assert_eq!(count_likes(&likes, None), 3);
+        // The cutoff itself still counts
+        assert_eq!(count_likes(&likes, Some(100)), 2);
+        assert_eq!(count_likes(&likes, Some(101)), 1);
+        assert_eq!(count_likes(&likes, Some(201)), 0);
+        assert_eq!(count_likes(&[], Some(0)), 0);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::util::consts::ADMINS;
-use crate::util::memory::{LAYOUTS, RemoveError};
+use crate::util::memory::{self, LAYOUTS, RemoveError};
 use crate::util::parser::{get_kwargs, KwargType};
 use crate::util::{Commandable, Message};
 use fxhash::FxHashMap;
@@ -18,7 +18,10 @@ impl Commandable for Command {
         let name = msg.arg;
         if !ADMINS.contains(&msg.id) {
             return match LAYOUTS.remove(name, msg.id) {
-                Ok(_) => format!("`{name}` has been removed"),
+                Ok(ll) => {
+                    memory::remove_layout_data(ll.id);
+                    format!("`{name}` has been removed")
+                }
                 Err(err) => err.to_string(),
             };
         }
@@ -31,7 +34,10 @@ impl Commandable for Command {
             false => LAYOUTS.remove(&kwarg.arg, msg.id),
         };
         match result {
-            Ok(_) => format!("`{}` has been removed", kwarg.arg),
+            Ok(ll) => {
+                memory::remove_layout_data(ll.id);
+                format!("`{}` has been removed", kwarg.arg)
+            }
             Err(err @ RemoveError::NotFound(_)) => err.to_string(),
             Err(err @ RemoveError::NotOwner(_)) =>
                 format!("{err}\nHelp: you may remove it with `remove {} --sudo`", kwarg.arg),
@@ -14,13 +14,15 @@ impl Commandable for Command {
         if let Err(err) = check_name(new) {
             return err;
         }
+        if LAYOUTS.contains(new) {
+            return format!("Error: `{new}` already exists")
+        }
         match LAYOUTS.remove(old, msg.id) {
             Err(err) => err.to_string(),
             Ok(mut layout) => {
-                if LAYOUTS.contains(new) {
-                    return format!("Error: `{new}` already exists")
-                }
+                layout.push_revision(msg.id);
                 layout.name = new.to_owned();
+                layout.display_name = None;
                 LAYOUTS.add(layout);  // Add always succeed
                 format!("`{old}` has been renamed to `{new}`")
             }
@@ -34,4 +36,8 @@ impl Commandable for Command {
     fn desc<'a>(&self) -> &'a str {
         "rename one of your layouts"
     }
+
+    fn records_author(&self) -> bool {
+        true
+    }
 }
\ No newline at end of file
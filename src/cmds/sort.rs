@@ -0,0 +1,110 @@
+use crate::util::analyzer::{is_lower_better, named_stat, NAMED_STATS};
+use crate::util::core::LayoutConfig;
+use crate::util::corpora::{corpus_ngrams, get_user_corpus};
+use crate::util::score::SCORE_MODELS;
+use crate::util::memory::LAYOUTS;
+use crate::util::parser::{get_kwargs, KwargType};
+use crate::util::{cache, Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+
+const LIST_COUNT: usize = 25;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("min-coverage".to_owned(), KwargType::Str),
+    ("penalize".to_owned(), KwargType::Bool),
+    ("reverse".to_owned(), KwargType::Bool),
+    ("preset".to_owned(), KwargType::Str),
+    ("tag".to_owned(), KwargType::Str),
+    ("lang".to_owned(), KwargType::Str),
+]));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let kwargs = match get_kwargs(msg.arg, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return err.to_string(),
+        };
+        let stat = kwargs.arg.to_lowercase();
+        if stat.is_empty() {
+            return self.help();
+        }
+        let is_score = stat == "score";
+        if !is_score && !NAMED_STATS.iter().any(|(name, _)| *name == stat) {
+            let names: Vec<&str> = NAMED_STATS.iter().map(|(name, _)| *name).collect();
+            return format!("Error: unknown stat `{stat}`, expected one of: score, {}", names.join(", "));
+        }
+        let (preset, model) = match SCORE_MODELS.get(kwargs["preset"].unwrap_str()) {
+            Ok(model) => model,
+            Err(err) => return err,
+        };
+        let min_coverage = match kwargs["min-coverage"].unwrap_str() {
+            None => 0.0,
+            Some(percent) => match percent.trim_end_matches('%').parse::<f64>() {
+                Ok(percent) => percent / 100.0,
+                Err(_) => return format!("Error: `{percent}` is not a valid percentage"),
+            }
+        };
+        let penalize = kwargs["penalize"].unwrap_bool();
+        let lower_better = is_lower_better(&stat) ^ kwargs["reverse"].unwrap_bool();
+        let tag = kwargs["tag"].unwrap_str().map(str::to_lowercase);
+        let language = kwargs["lang"].unwrap_str().map(str::to_lowercase);
+
+        let corpus = get_user_corpus(msg.id);
+        let monograms = corpus_ngrams::<1>(&corpus);
+        let layouts = LAYOUTS.read().unwrap();
+        let layouts: Vec<&LayoutConfig> = layouts.values()
+            .filter(|ll| tag.as_ref().is_none_or(|tag| ll.meta.tags.contains(tag)))
+            .filter(|ll| language.is_none() || ll.meta.language == language)
+            .collect();
+        let mut ranked: Vec<(&str, f64)> = layouts.par_iter()
+            .filter_map(|ll| {
+                let stats = cache::get_or_analyze(ll, &corpus);
+                let unknown = named_stat(&stats, "unknown").unwrap();
+                if 1.0 - unknown < min_coverage {
+                    return None;
+                }
+                let mut value = match is_score {
+                    true => model.score(ll, &stats, &monograms),
+                    false => named_stat(&stats, &stat).unwrap() * 100.0,
+                };
+                // Unknown trigrams count against the layout
+                if penalize && stat != "unknown" {
+                    match is_lower_better(&stat) {
+                        true => value += unknown * 100.0,
+                        false => value -= unknown * 100.0,
+                    }
+                }
+                Some((ll.name.as_str(), value))
+            })
+            .collect();
+        ranked.sort_by(|(name0, value0), (name1, value1)| {
+            let ordering = value0.total_cmp(value1);
+            let ordering = if lower_better { ordering } else { ordering.reverse() };
+            ordering.then_with(|| name0.cmp(name1))
+        });
+
+        let mut s = match is_score {
+            true => format!("```\nSCORE ({}, {preset}):\n", corpus.to_uppercase()),
+            false => format!("```\n{} ({}):\n", stat.to_uppercase(), corpus.to_uppercase()),
+        };
+        let unit = if is_score { "" } else { "%" };
+        for (index, (name, value)) in ranked.iter().take(LIST_COUNT).enumerate() {
+            s.push_str(&format!("{:>2}. {name:<24} {value:>6.2}{unit}\n", index + 1));
+        }
+        s.push_str("```");
+        s
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "sort <stat|score> [--min-coverage <percent>] [--penalize] [--reverse] [--preset <score preset>] [--tag <tag>] [--lang <language>]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "rank layouts by a stat, optionally excluding or penalizing layouts with unplaced keys"
+    }
+}
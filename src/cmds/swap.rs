@@ -0,0 +1,119 @@
+use crate::util::core::{Key, LayoutConfig};
+use crate::util::corpora::{get_user_corpus, ngrams};
+use crate::util::incremental::{CompiledCorpus, IncrementalAnalyzer};
+use crate::util::memory::LAYOUTS;
+use crate::util::parser::{get_kwargs, split_word, KwargType};
+use crate::util::score::SCORE_MODELS;
+use crate::util::{layout, Commandable, Message};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("save".to_owned(), KwargType::Str),
+]));
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let kwargs = match get_kwargs(msg.arg, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return err.to_string(),
+        };
+        let mut args = kwargs.arg.as_str();
+        let name = split_word(&mut args);
+        if name.is_empty() || args.is_empty() {
+            return self.help();
+        }
+        let swaps = match parse_swaps(args) {
+            Ok(swaps) => swaps,
+            Err(err) => return err,
+        };
+
+        let trigrams = ngrams::<3>(msg.id);
+        let monograms = ngrams::<1>(msg.id);
+        let compiled = CompiledCorpus::new(&trigrams);
+
+        // The stored layout must be released before the swapped copy can be saved
+        let (original, swapped, before, after, note) = {
+            // Saving a new layout needs the same confidence as the commands that change one
+            let found = match kwargs["save"].unwrap_str() {
+                Some(_) => LAYOUTS.find_confident(name),
+                None => LAYOUTS.find(name),
+            };
+            let ll = match found {
+                Ok(ll) => ll,
+                Err(err) => return err,
+            };
+            let mut analyzer = IncrementalAnalyzer::new(&compiled, &ll.keys);
+            let before = analyzer.stats();
+            for (key0, key1) in swaps.iter() {
+                let index0 = analyzer.index(*key0).filter(|index| analyzer.position(*index).is_some());
+                let index1 = analyzer.index(*key1).filter(|index| analyzer.position(*index).is_some());
+                let (Some(index0), Some(index1)) = (index0, index1) else {
+                    return format!("Error: `{}` does not have both `{key0}` and `{key1}`", ll.name);
+                };
+                analyzer.swap(index0, index1);
+            }
+            let original = LayoutConfig::new(ll.name.clone(), ll.user, ll.board.clone(), ll.keys.clone());
+            let swapped = LayoutConfig::new(ll.name.clone(), msg.id, ll.board.clone(), analyzer.layout());
+            (original, swapped, before, analyzer.stats(), ll.note(name))
+        };
+
+        let score_str = match SCORE_MODELS.get(None) {
+            Ok((preset, model)) => format!("Score: {:.2} -> {:.2} ({preset})\n",
+                                           model.score(&original, &before, &monograms),
+                                           model.score(&swapped, &after, &monograms)),
+            Err(_) => String::new(),
+        };
+        let save_str = match kwargs["save"].unwrap_str().map(str::to_lowercase) {
+            None => String::new(),
+            Some(new_name) => match layout::create(&new_name, msg.id, swapped.board.clone(), swapped.keys.clone()) {
+                Ok(()) => format!("Saved as `{new_name}`\n"),
+                Err(err) => format!("{err}\n"),
+            },
+        };
+
+        let swaps_str: Vec<String> = swaps.iter().map(|(key0, key1)| format!("{key0}-{key1}")).collect();
+        let matrix_str = layout::get_matrix_str(&swapped);
+        let diff_str = layout::get_stats_diff_str(&before, &after);
+        let corpus_name = get_user_corpus(msg.id).to_uppercase();
+        format!("{note}```\n\
+                 {} ({})\n\
+                 {matrix_str}\n\
+                 \n\
+                 {corpus_name}:\n\
+                 {diff_str}\
+                 \n\
+                 {score_str}\
+                 ```\n\
+                 {save_str}",
+                original.name, swaps_str.join(", "))
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "swap <layout> <key>-<key>... [--save <new name>]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "preview how swapping keys changes the stats of a layout, optionally saving it as a new layout"
+    }
+
+    fn records_author(&self) -> bool {
+        true
+    }
+}
+
+/// Reads swaps written as `a-b`, separated by whitespace
+fn parse_swaps(args: &str) -> Result<Vec<(Key, Key)>, String> {
+    args.split_whitespace()
+        .map(|swap| {
+            let keys: Vec<Key> = swap.to_lowercase().chars().collect();
+            match keys.as_slice() {
+                [key0, '-', key1] => Ok((*key0, *key1)),
+                _ => Err(format!("Error: expected swaps as `a-b`, got `{swap}`")),
+            }
+        })
+        .collect()
+}
@@ -6,15 +6,24 @@ pub struct Command;
 impl Commandable for Command {
     fn exec(&self, msg: &Message) -> String {
         let id = msg.id;
-        let ll = &*LAYOUTS.find(msg.arg);
+        // The layout is released before taking the likes, so that a waiting writer can't deadlock them
+        let (name, key) = match LAYOUTS.find_confident(msg.arg) {
+            Ok(ll) => (ll.name.clone(), ll.store_key()),
+            Err(err) => return err,
+        };
         {
             let mut likes = LIKES.write().unwrap();
-            if let Some(liked_layouts) = likes.get_mut(&ll.name) {
+            if let Some(liked_layouts) = likes.get_mut(&key) {
                 if let Some(idx) = liked_layouts
                     .iter()
-                    .position(|liked_id| *liked_id == id) {
+                    .position(|like| like.user == id) {
                     liked_layouts.remove(idx);
-                    return format!("You unliked {}. (Now at {} likes)", ll.name, liked_layouts.len());
+                    let like_count = liked_layouts.len();
+                    // An empty list would be reported by `check-data`
+                    if like_count == 0 {
+                        likes.remove(&key);
+                    }
+                    return format!("You unliked {name}. (Now at {like_count} likes)");
                 }
             }
         }
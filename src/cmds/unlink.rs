@@ -0,0 +1,37 @@
+use crate::cmds::link::check_owner;
+use crate::util::links;
+use crate::util::parser::split_word;
+use crate::util::{Commandable, Message};
+
+pub struct Command;
+
+impl Commandable for Command {
+    fn exec(&self, msg: &Message) -> String {
+        let mut arg = msg.arg;
+        let name = split_word(&mut arg).to_lowercase();
+        let url = arg.trim().trim_start_matches('<').trim_end_matches('>');
+        if name.is_empty() {
+            return self.help();
+        }
+        let id = match check_owner(&name, msg.id) {
+            Ok(id) => id,
+            Err(err) => return err,
+        };
+        let url = (!url.is_empty()).then_some(url);
+        match links::remove_links(id, &name, url) {
+            Ok(removed) => {
+                let removed: Vec<String> = removed.iter().map(|link| format!("<{link}>")).collect();
+                format!("Removed from `{name}`:\n{}", removed.join("\n"))
+            }
+            Err(err) => err,
+        }
+    }
+
+    fn usage<'a>(&self) -> &'a str {
+        "unlink <layout> [<url>]"
+    }
+
+    fn desc<'a>(&self) -> &'a str {
+        "remove a link of one of your layouts, or all of its links if no url is given"
+    }
+}
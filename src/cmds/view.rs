@@ -1,24 +1,86 @@
 use crate::util::Commandable;
-use crate::util::{layout, Message};
+use crate::util::{corpora, heatmap, layout, Message};
+use crate::util::score::SCORE_MODELS;
+use crate::util::core::{Attachment, Reply};
+use crate::util::layout::ViewOptions;
 use crate::util::memory::LAYOUTS;
+use crate::util::parser::{get_kwargs, KwargType};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+
+static KWARGS: Lazy<FxHashMap<String, KwargType>>
+= Lazy::new(|| FxHashMap::from_iter([
+    ("fingers".to_owned(), KwargType::Bool),
+    ("heatmap".to_owned(), KwargType::Bool),
+    ("arcs".to_owned(), KwargType::Bool),
+    ("preset".to_owned(), KwargType::Str),
+]));
 
 pub struct Command;
 
 impl Commandable for Command {
     fn exec(&self, msg: &Message) -> String {
-        let name = &msg.arg;
+        self.exec_reply(msg).content
+    }
+
+    fn exec_reply(&self, msg: &Message) -> Reply {
+        let kwargs = match get_kwargs(msg.arg, &KWARGS) {
+            Ok(kwargs) => kwargs,
+            Err(err) => return Reply::from(err.to_string()),
+        };
+        let name = &kwargs.arg;
         if name.is_empty() {
-            return self.help();
+            return Reply::from(self.help());
+        }
+        let preset = kwargs["preset"].unwrap_str();
+        if let Err(err) = SCORE_MODELS.get(preset) {
+            return Reply::from(err);
+        }
+        let options = ViewOptions {
+            fingers: kwargs["fingers"].unwrap_bool(),
+            preset: preset.map(str::to_owned),
+        };
+        // `<layout>@<rev>` views an earlier revision
+        let (name, rev) = match name.rsplit_once('@').map(|(name, rev)| (name, rev.parse::<usize>())) {
+            Some((name, Ok(rev))) => (name, Some(rev)),
+            _ => (name.as_str(), None),
+        };
+        let current = match LAYOUTS.find(name) {
+            Ok(current) => current,
+            Err(err) => return Reply::from(err),
+        };
+        let revision = match rev {
+            Some(rev) if rev == current.history.len() + 1 => None,
+            Some(rev) => match current.revision(rev) {
+                Some(revision) => Some(revision),
+                None => return Reply::from(format!("Error: `{}` has revisions 1 to {}",
+                                                   current.name, current.history.len() + 1)),
+            },
+            None => None,
+        };
+        let ll = revision.as_ref().unwrap_or(&*current);
+        let mut reply = Reply::from(current.note(name) + &layout::to_string_with(ll, msg.id, &options));
+        if revision.is_some() {
+            reply.content.push_str(&format!("Moved keys since then:\n```\n{}\n```",
+                                            layout::get_moved_str(&ll.keys, &current)));
+        }
+
+        if kwargs["heatmap"].unwrap_bool() {
+            let monograms = corpora::ngrams::<1>(msg.id);
+            let bigrams = kwargs["arcs"].unwrap_bool().then(|| corpora::ngrams::<2>(msg.id));
+            reply.files.push(Attachment {
+                filename: format!("{}-heatmap.png", ll.name),
+                data: heatmap::render(ll, &monograms, bigrams.as_deref()),
+            });
         }
-        let ll = &*LAYOUTS.find(name);
-        layout::to_string(ll, msg.id)
+        reply
     }
 
     fn usage<'a>(&self) -> &'a str {
-        "view <layout name>"
+        "view <layout name>[@<revision>] [--fingers] [--heatmap [--arcs]] [--preset <score preset>]"
     }
 
     fn desc<'a>(&self) -> &'a str {
-        "see the stats of a layout"
+        "see the stats of a layout, optionally with a per-finger breakdown or a heatmap image"
     }
-}
\ No newline at end of file
+}
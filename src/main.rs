@@ -11,7 +11,7 @@ mod util;
 use once_cell::sync::Lazy;
 use serenity::async_trait;
 use serenity::client::{Client, Context, EventHandler};
-use serenity::model::channel::Message as DiscordMessage;
+use serenity::model::channel::{AttachmentType, Message as DiscordMessage};
 use serenity::model::gateway::{GatewayIntents, Ready};
 use std::io::Write;
 use std::sync::{Arc, RwLock};
@@ -19,7 +19,9 @@ use std::fs;
 use tokio::signal;
 use tokio::time::{self, Duration};
 
-use crate::util::consts::{ADMINS, CMINI_CHANNEL, TRIGGERS};
+use crate::util::authors::AUTHORS;
+use crate::util::consts::{ADMINS, CMINI_CHANNEL, MAX_ATTACHMENT_SIZE, TRIGGERS};
+use crate::util::core::{Attachment, Reply};
 use crate::util::{validate_json, Message};
 
 static MAINTENANCE_MODE: Lazy<Arc<RwLock<bool>>> = Lazy::new(|| Arc::new(RwLock::new(false)));
@@ -34,10 +36,32 @@ fn maintenance_check(id: u64) -> bool {
 
 struct Handler;
 
+/// Saves the username and server nickname of the author, the first name seen staying the canonical one
+fn record_author(msg: &Message) {
+    let mut authors = AUTHORS.write().unwrap();
+    authors.update(msg.id, msg.author.name.clone());
+    if let Some(nick) = msg.member.as_ref().and_then(|member| member.nick.clone()) {
+        authors.update(msg.id, nick);
+    }
+}
+
+async fn download_attachments(msg: &DiscordMessage) -> Vec<Attachment> {
+    let mut files = Vec::new();
+    for attachment in msg.attachments.iter().filter(|attachment| attachment.size <= MAX_ATTACHMENT_SIZE) {
+        if let Ok(data) = attachment.download().await {
+            files.push(Attachment {
+                filename: attachment.filename.clone(),
+                data,
+            });
+        }
+    }
+    files
+}
+
 #[async_trait]
 impl EventHandler for Handler {
     async fn message(&self, ctx: Context, msg: DiscordMessage) {
-        let msg = Message::from(&msg);
+        let mut msg = Message::from(&msg);
 
         // Ignore other bots and empty messages
         if msg.author.bot || msg.content.is_empty() {
@@ -65,32 +89,48 @@ impl EventHandler for Handler {
         let mut cmini_channel_only = false;
         let response = match action {
             "" => {
-                "Try `!cmini help`".to_owned()
+                Reply::from("Try `!cmini help`".to_owned())
             }
             "akl" => {
-                "Not yet implemented".to_owned()
+                Reply::from("Not yet implemented".to_owned())
             },
             "maintenance" | "1984" => {
-                cmds::maintenance::Command.exec(msg.arg, id, Arc::clone(&MAINTENANCE_MODE))
+                Reply::from(cmds::maintenance::Command.exec(msg.arg, id, Arc::clone(&MAINTENANCE_MODE)))
             }
             _ => {
                 match cmds::get_cmd(action) {
                     Some(cmd) => {
                         cmini_channel_only = cmd.cmini_channel_only();
-                        cmd.try_exec(&msg)
+                        if cmd.records_author() {
+                            record_author(&msg);
+                        }
+                        if cmd.reads_attachments() {
+                            msg.files = download_attachments(&msg).await;
+                        }
+                        // Commands are synchronous, and some can take seconds
+                        tokio::task::block_in_place(|| cmd.try_exec(&msg))
                     },
-                    None => format!("Error: {} is not an available command", &action),
+                    None => Reply::from(format!("Error: {} is not an available command", &action)),
                 }
             }
         };
 
         // DM required?
-        match !in_cmini_channel && cmini_channel_only {
-            true => if let Ok(dm_channel) = msg.author.create_dm_channel(&ctx.http).await {
-                let _ = dm_channel.say(&ctx.http, &response).await;
+        let channel_id = match !in_cmini_channel && cmini_channel_only {
+            true => match msg.author.create_dm_channel(&ctx.http).await {
+                Ok(dm_channel) => dm_channel.id,
+                Err(_) => return,
             }
-            false => { let _ = msg.channel_id.say(&ctx.http, &response).await; }
-        }
+            false => msg.channel_id,
+        };
+        let files = response.files.into_iter().map(|file| AttachmentType::Bytes {
+            data: file.data.into(),
+            filename: file.filename,
+        });
+        // Replies echo descriptions and names that users wrote, which must not ping anyone
+        let _ = channel_id.send_message(&ctx.http, |m| {
+            m.content(&response.content).add_files(files).allowed_mentions(|mentions| mentions.empty_parse())
+        }).await;
     }
 
     async fn ready(&self, _: Context, ready: Ready) {
@@ -135,12 +175,27 @@ async fn start_discord_bot() {
 #[tokio::main]
 async fn main() {
     validate_json();
+    // Likes, links and cached stats saved by name before layouts had ids
+    let moved = util::consistency::migrate_name_keys();
+    if moved > 0 {
+        println!("Moved {moved} entries of likes, links and cached stats from layout names to ids");
+        util::memory::sync_data();
+        util::jsons::write_json("./cached_stats.json", &*util::cache::CACHED_STATS);
+    }
 
     let args: Vec<String> = std::env::args().collect();
     if !args.is_empty() && args.contains(&String::from("cache")) {
         util::cache::cache_main();
         return;
     }
+    if let Some(index) = args.iter().position(|arg| arg == "import") {
+        util::import::import_main(&args[index + 1..]);
+        return;
+    }
+    if let Some(index) = args.iter().position(|arg| arg == "layouts") {
+        util::layout_files::layouts_main(&args[index + 1..]);
+        return;
+    }
 
     tokio::spawn(daily_cron_job());
     tokio::spawn(start_discord_bot());
@@ -1,3 +1,4 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 pub mod test {
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use fxhash::FxHashMap;
-use crate::util::consts::TABLE;
-use crate::util::core::{Finger, FingerUsage, Key, Layout, Metric, LayoutConfig, Stat};
+use crate::util::consts::{NUMBER_ROW, TABLE};
+use crate::util::corpora::merge_grams;
+use crate::util::core::{Finger, FingerUsage, Key, Layout, Metric, LayoutConfig, Position, Row, Stat};
 
 pub fn fingers_usage(ll: &LayoutConfig, grams: &[([Key; 1], u64)]) -> FingerUsage {
     let mut fingers: FxHashMap<Finger, u64> = FxHashMap::default();
@@ -34,6 +35,99 @@ pub fn fingers_usage(ll: &LayoutConfig, grams: &[([Key; 1], u64)]) -> FingerUsag
     fingers
 }
 
+/// Same finger bigrams of each finger, as a share of all bigrams
+pub fn fingers_sfb(ll: &LayoutConfig, grams: &[([Key; 2], u64)]) -> FingerUsage {
+    let mut fingers: FingerUsage = FingerUsage::default();
+    let mut total = 0u64;
+
+    for ([gram0, gram1], count) in grams.iter() {
+        if *gram0 == ' ' || *gram1 == ' ' {
+            continue;
+        }
+        total += count;
+        if gram0 == gram1 {
+            continue;
+        }
+        let (Some(pos0), Some(pos1)) = (ll.keys.get(gram0), ll.keys.get(gram1)) else {
+            continue;
+        };
+        if pos0.2 == pos1.2 {
+            *fingers.entry(pos0.2).or_insert(0.0) += *count as f64;
+        }
+    }
+    fingers.values_mut().for_each(|freq| *freq /= total as f64);
+    fingers
+}
+
+/// Keys of space between the hands of a split board
+pub const SPLIT_GAP: f64 = 2.0;
+
+/// Physical position of a key in key units, taking the row stagger of the board into account.
+/// Angle boards only shift the bottom row, the way `view` shows them, and split boards
+/// move the right hand `SPLIT_GAP` keys away.
+pub fn key_coords(board: &str, (row, col, _): &Position) -> (f64, f64) {
+    let stagger = match (board, row) {
+        ("stagger", 1) => 0.25,
+        ("stagger", 2) => 0.75,
+        ("stagger", &NUMBER_ROW) => -0.5,
+        ("angle", 2) => 0.5,
+        _ => 0.0,
+    };
+    let gap = if board == "split" && *col >= 5 { SPLIT_GAP } else { 0.0 };
+    let y = if *row == NUMBER_ROW { -1.0 } else { f64::from(*row) };
+    (f64::from(*col) + stagger + gap, y)
+}
+
+/// Distance travelled by each finger on same finger bigrams, in keys per bigram
+pub fn fingers_distance(ll: &LayoutConfig, grams: &[([Key; 2], u64)]) -> FingerUsage {
+    let mut fingers: FingerUsage = FingerUsage::default();
+    let mut total = 0u64;
+
+    for ([gram0, gram1], count) in grams.iter() {
+        if *gram0 == ' ' || *gram1 == ' ' {
+            continue;
+        }
+        total += count;
+        let (Some(pos0), Some(pos1)) = (ll.keys.get(gram0), ll.keys.get(gram1)) else {
+            continue;
+        };
+        if pos0.2 != pos1.2 {
+            continue;
+        }
+        let (x0, y0) = key_coords(&ll.board, pos0);
+        let (x1, y1) = key_coords(&ll.board, pos1);
+        let distance = ((x0 - x1).powi(2) + (y0 - y1).powi(2)).sqrt();
+        *fingers.entry(pos0.2).or_insert(0.0) += *count as f64 * distance;
+    }
+    fingers.values_mut().for_each(|dist| *dist /= total as f64);
+    fingers
+}
+
+/// Usage of each row, ignoring keys that the layout doesn't place
+pub fn rows_usage(ll: &LayoutConfig, grams: &[([Key; 1], u64)]) -> FxHashMap<Row, f64> {
+    let mut rows: FxHashMap<Row, f64> = FxHashMap::default();
+    let mut total = 0u64;
+
+    for ([gram], count) in grams.iter() {
+        if let Some((row, _, _)) = ll.keys.get(gram) {
+            *rows.entry(*row).or_insert(0.0) += *count as f64;
+            total += count;
+        }
+    }
+    rows.values_mut().for_each(|freq| *freq /= total as f64);
+    rows
+}
+
+/// Keys in the corpus that the layout doesn't place, sorted by frequency
+pub fn missing_keys(ll: &LayoutConfig, grams: &[([Key; 1], u64)]) -> Vec<(Key, f64)> {
+    let grams = merge_grams(grams);
+    let total = grams.iter().map(|(_, count)| count).sum::<u64>() as f64;
+
+    grams.into_iter()
+        .filter(|([key], _)| *key != ' ' && !ll.keys.contains_key(key))
+        .map(|([key], count)| (key, count as f64 / total))
+        .collect()
+}
 
 pub fn trigrams(ll: &LayoutConfig, grams: &[([Key; 3], u64)]) -> Stat {
     let mut counter = Metric::new_counter();
@@ -78,3 +172,61 @@ fn get_finger_hash(layout: &Layout, gram0: Key, gram1: Key, gram2: Key) -> Optio
     let finger2 = layout.get(&gram2)?.2;
     Some((finger0 << 8) | (finger1 << 4) | finger2)
 }
+
+/// Stats that can be looked up by name, and whether lower values are better
+pub const NAMED_STATS: [(&str, bool); 15] = [
+    ("alt", false),
+    ("roll", false),
+    ("inroll", false),
+    ("outroll", false),
+    ("one", false),
+    ("inone", false),
+    ("outone", false),
+    ("rolltal", false),
+    ("red", true),
+    ("bad-red", true),
+    ("sfb", true),
+    ("sfs", true),
+    ("red-sfs", true),
+    ("alt-sfs", true),
+    ("unknown", true),
+];
+
+/// Looks up a stat as displayed by `view`, combining metrics where needed
+pub fn named_stat(stats: &Stat, name: &str) -> Option<f64> {
+    use Metric as M;
+    let get = |metric: M| -> f64 {
+        *stats.get(&metric).unwrap_or(&0.0)
+    };
+    let bad_red = get(M::BadRed) + get(M::BadRedSfs);
+    let red_sfs = get(M::RedSfs) + get(M::BadRedSfs);
+    let roll = get(M::InRoll) + get(M::OutRoll);
+    let one = get(M::InOne) + get(M::OutOne);
+
+    let stat = match name {
+        "alt" => get(M::Alt),
+        "roll" => roll,
+        "inroll" => get(M::InRoll),
+        "outroll" => get(M::OutRoll),
+        "one" => one,
+        "inone" => get(M::InOne),
+        "outone" => get(M::OutOne),
+        "rolltal" => roll + one,
+        "red" => get(M::Red) + bad_red,
+        "bad-red" => bad_red,
+        "sfb" => get(M::Sfb) / 2.0,
+        "sfs" => get(M::AltSfs) + red_sfs,
+        "red-sfs" => red_sfs,
+        "alt-sfs" => get(M::AltSfs),
+        "unknown" => get(M::Unknown),
+        _ => return None,
+    };
+    Some(stat)
+}
+
+/// Whether a lower value of the named stat is better
+pub fn is_lower_better(name: &str) -> bool {
+    NAMED_STATS.iter()
+        .find(|(stat_name, _)| *stat_name == name)
+        .is_some_and(|(_, lower_better)| *lower_better)
+}
@@ -10,6 +10,25 @@ pub static AUTHORS: Lazy<Arc<RwLock<Authors>>> = Lazy::new(||
     Arc::new(RwLock::new(Authors::open("./authors.json").unwrap()))
 );
 
+/// Names at least this similar to the searched name count as a match
+const MIN_SIMILARITY: f64 = 0.85;
+/// A match is ambiguous when another author is at most this much less similar
+const AMBIGUITY_MARGIN: f64 = 0.03;
+/// Names at least this similar are suggested when nothing matches
+const MIN_SUGGESTED_SIMILARITY: f64 = 0.7;
+const MAX_CANDIDATES: usize = 5;
+
+/// ID of a `<@id>` or `<@!id>` mention
+pub fn parse_mention(s: &str) -> Option<u64> {
+    s.strip_prefix("<@")?.strip_suffix('>')?.trim_start_matches('!').parse().ok()
+}
+
+/// Whether a typed number could be a Discord ID, whose bits above the lowest 22 hold
+/// the milliseconds since 2015, so that every ID of a real user has at least 17 digits
+pub fn is_discord_id(id: u64) -> bool {
+    id >= 10_000_000_000_000_000
+}
+
 pub struct Authors {
     id_to_str: FxHashMap<u64, Vec<String>>,
     str_to_id: FxHashMap<String, u64>,
@@ -47,6 +66,16 @@ impl Authors {
         Some(&names[0])
     }
 
+    /// Author who goes by exactly `name`
+    pub fn get_owner(&self, name: &str) -> Option<u64> {
+        self.str_to_id.get(name).copied()
+    }
+
+    /// Every name of an author, the first being the canonical name
+    pub fn get_names(&self, id: u64) -> Option<&[String]> {
+        Some(self.id_to_str.get(&id)?)
+    }
+
     pub fn get_id(&self, name: &str) -> u64 {
         self.str_to_id
             .iter()
@@ -59,7 +88,64 @@ impl Authors {
             .unwrap()  // id_to_str is always non-empty
     }
 
+    /// Authors with a name similar to `name`, most similar first, ignoring case
+    pub fn candidates(&self, name: &str) -> Vec<(u64, f64)> {
+        let name = name.to_lowercase();
+        let mut best: FxHashMap<u64, f64> = FxHashMap::default();
+        for (other_name, id) in self.str_to_id.iter() {
+            let score = jaro_winkler(&other_name.to_lowercase(), &name);
+            let best_score = best.entry(*id).or_default();
+            *best_score = best_score.max(score);
+        }
+        let mut candidates: Vec<(u64, f64)> = best.into_iter().collect();
+        candidates.sort_by(|(id0, score0), (id1, score1)| score1.total_cmp(score0).then(id0.cmp(id1)));
+        candidates
+    }
+
+    fn candidates_str(&self, candidates: &[(u64, f64)]) -> String {
+        let candidates: Vec<String> = candidates.iter().take(MAX_CANDIDATES)
+            .map(|(id, _)| format!("{} (`{id}`)", self.get_name(*id).unwrap_or_default()))
+            .collect();
+        candidates.join(", ")
+    }
+
+    /// Finds an author by ID, mention or name, where a misspelt name
+    /// must be close to the name of exactly one author
+    pub fn resolve(&self, query: &str) -> Result<u64, String> {
+        let query = query.trim();
+        if let Some(id) = parse_mention(query).or_else(|| query.parse().ok()) {
+            return match self.id_to_str.contains_key(&id) {
+                true => Ok(id),
+                false => Err(format!("Error: author with ID `{id}` does not exist")),
+            };
+        }
+        let candidates = self.candidates(query);
+        let Some(&(id, score)) = candidates.first() else {
+            return Err(format!("Error: no author is called `{query}`"));
+        };
+        if score < MIN_SIMILARITY {
+            let suggested = candidates.partition_point(|(_, score)| *score >= MIN_SUGGESTED_SIMILARITY);
+            return match suggested {
+                0 => Err(format!("Error: no author is called `{query}`")),
+                _ => Err(format!("Error: no author is called `{query}`, did you mean: {}",
+                                 self.candidates_str(&candidates[..suggested]))),
+            };
+        }
+        // An exact name is only ambiguous with another exact name
+        let margin = if score == 1.0 { 0.0 } else { AMBIGUITY_MARGIN };
+        let close = candidates.partition_point(|(_, other_score)| *other_score >= score - margin);
+        if close > 1 {
+            return Err(format!("Error: `{query}` could be any of these authors, use their ID or a mention: {}",
+                               self.candidates_str(&candidates[..close])));
+        }
+        Ok(id)
+    }
+
+    /// Adds a name of an author, unless another author already goes by it
     pub fn update(&mut self, id: u64, new_name: String) {
+        if self.str_to_id.get(&new_name).is_some_and(|owner| *owner != id) {
+            return;
+        }
         match self.id_to_str.get_mut(&id) {
             None => {
                 // New author
@@ -72,8 +158,19 @@ impl Authors {
                 return;
             }
         }
-        // Always keep the existing name first
-        self.str_to_id.entry(new_name).or_insert(id);
+        self.str_to_id.insert(new_name, id);
+    }
+
+    /// Makes `name` the canonical name of an author, keeping the other names as aliases
+    pub fn set_name(&mut self, id: u64, name: String) -> Result<(), String> {
+        if self.str_to_id.get(&name).is_some_and(|owner| *owner != id) {
+            return Err(format!("Error: `{name}` is the name of another author"));
+        }
+        let names = self.id_to_str.entry(id).or_default();
+        names.retain(|old_name| *old_name != name);
+        names.insert(0, name.clone());
+        self.str_to_id.insert(name, id);
+        Ok(())
     }
 }
 
@@ -91,3 +188,46 @@ impl Serialize for Authors {
         self.id_to_str.serialize(serializer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        let mut authors = Authors::open("./authors.json").unwrap();
+        let semi = authors.get_id("semi");
+        authors.update(1, "newcomer".to_owned());
+        authors.update(1, "Newcomer".to_owned());
+        assert_eq!(authors.get_names(1), Some(&["newcomer".to_owned(), "Newcomer".to_owned()][..]));
+
+        authors.set_name(1, "Newcomer".to_owned()).unwrap();
+        assert_eq!(authors.get_name(1), Some("Newcomer"));
+        assert_eq!(authors.get_names(1).unwrap().len(), 2);
+        assert!(authors.set_name(1, "semi".to_owned()).is_err());
+        assert_eq!(authors.get_id("semi"), semi);
+
+        // The name of another author is not taken as an alias
+        authors.update(1, "semi".to_owned());
+        authors.update(2, "semi".to_owned());
+        assert_eq!(authors.get_names(1).unwrap().len(), 2);
+        assert_eq!(authors.get_names(2), None);
+        assert_eq!(authors.resolve("semi"), Ok(semi));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let authors = AUTHORS.read().unwrap();
+        let semi = authors.get_id("semi");
+        assert_eq!(authors.resolve("semi"), Ok(semi));
+        assert_eq!(authors.resolve("SEMI"), Ok(semi));
+        assert_eq!(authors.resolve(&format!("<@{semi}>")), Ok(semi));
+        assert_eq!(authors.resolve(&format!("<@!{semi}>")), Ok(semi));
+        assert_eq!(authors.resolve("galileotime"), authors.resolve("GalileoBlues"));
+        assert!(authors.resolve("zzzzzzzz").is_err());
+        assert!(authors.resolve("5").is_err());
+        assert_eq!(authors.get_owner("semi"), Some(semi));
+        assert_eq!(authors.get_owner("Semi"), None);
+        assert!(is_discord_id(semi) && !is_discord_id(12345));
+    }
+}
@@ -1,5 +1,6 @@
-use crate::util::core::{CachedStatConfig, CachedStats, LayoutConfig, RawCachedStatConfig, ServerCachedStats, Stat};
+use crate::util::core::{CachedStatConfig, CachedStats, LayoutConfig, LayoutId, RawCachedStatConfig, ServerCachedStats, Stat};
 use crate::util::jsons::{get_server_cached_stats, write_json};
+use crate::util::incremental::{CompiledCorpus, IncrementalAnalyzer};
 use crate::util::{analyzer, corpora, memory};
 use once_cell::sync::Lazy;
 use rayon::prelude::*;
@@ -7,42 +8,59 @@ use std::sync::Arc;
 use std::time::Instant;
 use crate::util::memory::LAYOUTS;
 
+/// Stats of each layout by layout id
 pub static CACHED_STATS: Lazy<ServerCachedStats> = Lazy::new(|| get_server_cached_stats("./cached_stats.json"));
 
-pub fn get(name: &str, corpus: &str) -> Option<Arc<Stat>> {
-    if name.is_empty() || corpus.is_empty() {
+pub fn get(id: LayoutId, corpus: &str) -> Option<Arc<Stat>> {
+    if corpus.is_empty() {
         return None;
     }
-    let name = name.to_lowercase();
     let corpus = corpus.to_lowercase();
 
     let cached_stats = CACHED_STATS.read().unwrap();
-    let stats = cached_stats.get(&name)?.stats.get(&corpus)?;
+    let stats = cached_stats.get(&id.to_string())?.stats.get(&corpus)?;
     Some(Arc::clone(stats))
 }
 
-fn get_cache(name: &str) -> Option<CachedStatConfig> {
-    let cached_stats = CACHED_STATS.read().unwrap();
-    let name = name.to_lowercase();
-    Some(Arc::clone(cached_stats.get(&name)?))
+/// Gets the stats of a layout from the cache, analyzing it instead if the cache is outdated
+pub fn get_or_analyze(ll: &LayoutConfig, corpus: &str) -> Arc<Stat> {
+    if let Some(cached) = get_cache(&ll.store_key()) {
+        if let Some(stats) = cached.stats.get(corpus).filter(|_| cached.sum == ll.sum) {
+            return Arc::clone(stats);
+        }
+    }
+    let trigrams = corpora::corpus_ngrams::<3>(corpus);
+    Arc::new(analyzer::trigrams(ll, &trigrams))
+}
+
+/// Drops the cached stats of a layout whose keys changed, until the next `cache` run
+pub fn invalidate(id: LayoutId) {
+    let mut cached_stats = CACHED_STATS.write().unwrap();
+    cached_stats.shift_remove(&id.to_string());
 }
 
-fn cache_fill(ll: &LayoutConfig, data: &mut CachedStats, corpus: &str) {
-    let path = format!("./corpora/{}/trigrams.json", corpus);
-    let trigrams = corpora::load_corpus(&path);
-    let stats = analyzer::trigrams(ll, &trigrams);
+fn get_cache(key: &str) -> Option<CachedStatConfig> {
+    let cached_stats = CACHED_STATS.read().unwrap();
+    Some(Arc::clone(cached_stats.get(key)?))
+}
 
+fn cache_fill(ll: &LayoutConfig, data: &mut CachedStats, corpus: &str, compiled: &CompiledCorpus) {
+    let stats = IncrementalAnalyzer::new(compiled, &ll.keys).stats();
     data.insert(corpus.to_string(), Arc::new(stats));
 }
 
-fn update(name: &str, data: CachedStatConfig) {
+fn update(key: String, data: CachedStatConfig) {
     let mut cached_stats = CACHED_STATS.write().unwrap();
-    cached_stats.insert(name.to_string(), data);
+    cached_stats.insert(key, data);
 }
 
+/// Sorts by layout id, keeping keys that are not ids at the end
 fn sort() {
     let mut cached_stats = CACHED_STATS.write().unwrap();
-    cached_stats.sort_keys();
+    cached_stats.sort_by(|key0, _, key1, _| {
+        let id = |key: &str| key.parse::<LayoutId>().unwrap_or(LayoutId::MAX);
+        id(key0).cmp(&id(key1)).then_with(|| key0.cmp(key1))
+    });
 }
 
 fn cache_files() {
@@ -55,11 +73,18 @@ fn cache_files() {
             Some(file.path().file_name()?.to_str()?.to_string())
         })
     }).collect();
+    // Compiling each corpus once makes analyzing a layout a pass over flat arrays
+    let compiled: Vec<CompiledCorpus> = corpus_names.par_iter()
+        .map(|corpus| {
+            let path = format!("./corpora/{}/trigrams.json", corpus);
+            CompiledCorpus::new(&corpora::load_corpus(&path))
+        })
+        .collect();
 
     names.par_iter().for_each(|name| {
         // let layout_start = Instant::now();
         let ll = &*LAYOUTS.get(name);
-        let cached = get_cache(name);
+        let cached = get_cache(&ll.store_key());
         if let Some(cached) = &cached {
             if cached.sum == ll.sum {
                 println!("Layout: {}", &ll.name);
@@ -69,15 +94,15 @@ fn cache_files() {
 
         let mut stats: CachedStats = CachedStats::default();
 
-        for corpus in corpus_names.iter() {
+        for (corpus, compiled) in corpus_names.iter().zip(compiled.iter()) {
             println!("Layout: {}, Corpus: {}", &ll.name, corpus);
-            cache_fill(ll, &mut stats, corpus);
+            cache_fill(ll, &mut stats, corpus, compiled);
         }
         let cached = RawCachedStatConfig {
             sum: ll.sum,
             stats,
         };
-        update(name, Arc::new(cached));
+        update(ll.store_key(), Arc::new(cached));
     });
     sort();
 
@@ -0,0 +1,210 @@
+use crate::util::cache::CACHED_STATS;
+use crate::util::core::LayoutId;
+use crate::util::links::{Links, LINKS, MAX_LINKS};
+use crate::util::memory::{assign_ids, LAYOUTS, LIKES};
+use fxhash::{FxHashMap, FxHashSet};
+use std::fmt::{Display, Formatter};
+
+/// Entries of the json stores that don't belong to a layout, or that a layout shares
+#[derive(Debug, Default, PartialEq)]
+pub struct Issues {
+    /// Keys of `likes.json` that are not the id of a layout
+    pub orphan_likes: Vec<String>,
+    /// Layouts with an empty like list
+    pub empty_likes: Vec<String>,
+    /// Layouts liked more than once by the same user
+    pub duplicate_likes: Vec<String>,
+    /// Keys of `links.json` that are not the id of a layout
+    pub orphan_links: Vec<String>,
+    /// Keys of `cached_stats.json` that are not the id of a layout
+    pub orphan_stats: Vec<String>,
+    /// Ids given to more than one layout, with the names of those layouts
+    pub duplicate_ids: Vec<(LayoutId, Vec<String>)>,
+}
+
+impl Issues {
+    pub fn count(&self) -> usize {
+        self.orphan_likes.len() + self.empty_likes.len() + self.duplicate_likes.len()
+            + self.orphan_links.len() + self.orphan_stats.len() + self.duplicate_ids.len()
+    }
+}
+
+/// Shows at most this many entries of each kind of issue
+const MAX_SHOWN: usize = 8;
+
+fn write_issue(f: &mut Formatter<'_>, store: &str, issue: &str, keys: &[String]) -> std::fmt::Result {
+    if keys.is_empty() {
+        return Ok(());
+    }
+    let shown: Vec<String> = keys.iter().take(MAX_SHOWN).map(|key| format!("`{key}`")).collect();
+    let more = match keys.len().saturating_sub(MAX_SHOWN) {
+        0 => String::new(),
+        more => format!(" and {more} more"),
+    };
+    writeln!(f, "{store}: {} {issue}: {}{more}", keys.len(), shown.join(", "))
+}
+
+impl Display for Issues {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_issue(f, "likes.json", "entries of missing layouts", &self.orphan_likes)?;
+        write_issue(f, "likes.json", "empty like lists", &self.empty_likes)?;
+        write_issue(f, "likes.json", "layouts liked twice by a user", &self.duplicate_likes)?;
+        write_issue(f, "links.json", "entries of missing layouts", &self.orphan_links)?;
+        write_issue(f, "cached_stats.json", "entries of missing layouts", &self.orphan_stats)?;
+        let duplicate_ids: Vec<String> = self.duplicate_ids.iter()
+            .map(|(id, names)| format!("{id} ({})", names.join(", ")))
+            .collect();
+        write_issue(f, "layouts.json", "ids shared by layouts", &duplicate_ids)
+    }
+}
+
+/// Checks that every like, link and cached stat belongs to a layout
+pub fn find_issues() -> Issues {
+    let mut names_of_id: FxHashMap<LayoutId, Vec<String>> = FxHashMap::default();
+    for ll in LAYOUTS.read().unwrap().values() {
+        names_of_id.entry(ll.id).or_default().push(ll.name.clone());
+    }
+    let name_of = |key: &str| -> Option<&str> {
+        let names = names_of_id.get(&key.parse::<LayoutId>().ok()?)?;
+        Some(&names[0])
+    };
+
+    let mut issues = Issues::default();
+    for (key, liked_users) in LIKES.read().unwrap().iter() {
+        let Some(name) = name_of(key) else {
+            issues.orphan_likes.push(key.clone());
+            continue;
+        };
+        if liked_users.is_empty() {
+            issues.empty_likes.push(name.to_owned());
+        }
+        if liked_users.iter().map(|like| like.user).collect::<FxHashSet<_>>().len() != liked_users.len() {
+            issues.duplicate_likes.push(name.to_owned());
+        }
+    }
+    issues.orphan_links = LINKS.read().unwrap().keys()
+        .filter(|key| name_of(key).is_none())
+        .cloned()
+        .collect();
+    issues.orphan_stats = CACHED_STATS.read().unwrap().keys()
+        .filter(|key| name_of(key).is_none())
+        .cloned()
+        .collect();
+    issues.duplicate_ids = names_of_id.iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(id, names)| (*id, names.clone()))
+        .collect();
+
+    issues.orphan_likes.sort();
+    issues.empty_likes.sort();
+    issues.duplicate_likes.sort();
+    issues.orphan_links.sort();
+    issues.orphan_stats.sort();
+    issues.duplicate_ids.sort();
+    issues
+}
+
+/// Removes the orphaned entries and double likes, and gives new ids to the layouts sharing one.
+/// The first layout of `layouts.json` sharing an id keeps it.
+pub fn repair(issues: &Issues) {
+    {
+        let mut layouts = LAYOUTS.write().unwrap();
+        for (_, names) in issues.duplicate_ids.iter() {
+            for name in names.iter().skip(1) {
+                if let Some(ll) = layouts.get_mut(name) {
+                    ll.id = 0;
+                }
+            }
+        }
+        assign_ids(&mut layouts);
+    }
+    {
+        let mut likes = LIKES.write().unwrap();
+        likes.retain(|key, liked_users| !issues.orphan_likes.contains(key) && !liked_users.is_empty());
+        for liked_users in likes.values_mut() {
+            let mut seen: FxHashSet<u64> = FxHashSet::default();
+            liked_users.retain(|like| seen.insert(like.user));
+        }
+    }
+    LINKS.write().unwrap().retain(|key, _| !issues.orphan_links.contains(key));
+    CACHED_STATS.write().unwrap().retain(|key, _| !issues.orphan_stats.contains(key));
+}
+
+/// Keys of a store that are the name of a layout rather than an id, with the id they move to.
+/// A key that is the id of a layout stays, even if another layout has it as its name.
+fn name_keys<'a>(keys: impl Iterator<Item = &'a String>, ids: &FxHashMap<String, String>) -> Vec<(String, String)> {
+    let is_id: FxHashSet<&String> = ids.values().collect();
+    keys.filter(|key| !is_id.contains(key))
+        .filter_map(|key| Some((key.clone(), ids.get(key)?.clone())))
+        .collect()
+}
+
+/// Moves the likes, links and cached stats still stored under the name of a layout to its id,
+/// the way they were stored before layouts had ids. Returns how many entries were moved.
+pub fn migrate_name_keys() -> usize {
+    let ids: FxHashMap<String, String> = LAYOUTS.read().unwrap().values()
+        .map(|ll| (ll.name.clone(), ll.store_key()))
+        .collect();
+    let mut moved = 0;
+    {
+        let mut likes = LIKES.write().unwrap();
+        for (name, id) in name_keys(likes.keys(), &ids) {
+            let liked_users = likes.remove(&name).unwrap();
+            let entry = likes.entry(id).or_default();
+            for like in liked_users {
+                if !entry.iter().any(|liked| liked.user == like.user) {
+                    entry.push(like);
+                }
+            }
+            moved += 1;
+        }
+    }
+    {
+        let mut links = LINKS.write().unwrap();
+        for (name, id) in name_keys(links.keys(), &ids) {
+            let Links(urls) = links.remove(&name).unwrap();
+            let entry = links.entry(id).or_default();
+            for url in urls {
+                if !entry.0.contains(&url) && entry.0.len() < MAX_LINKS {
+                    entry.0.push(url);
+                }
+            }
+            moved += 1;
+        }
+    }
+    {
+        // Stats already cached under the id are the newer ones
+        let mut cached_stats = CACHED_STATS.write().unwrap();
+        for (name, id) in name_keys(cached_stats.keys(), &ids) {
+            let stats = cached_stats.shift_remove(&name).unwrap();
+            if !cached_stats.contains_key(&id) {
+                cached_stats.insert(id, stats);
+            }
+            moved += 1;
+        }
+    }
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_issues() {
+        let issues = find_issues();
+        assert_eq!(issues.duplicate_ids, Vec::new());
+        assert_eq!(issues.orphan_stats, Vec::<String>::new());
+        assert!(!issues.orphan_likes.iter().any(|key| key.parse::<LayoutId>().is_ok()));
+        assert!(issues.to_string().lines().all(|line| line.len() < 400));
+    }
+
+    #[test]
+    fn test_name_keys() {
+        let ids = FxHashMap::from_iter([("semimak", "7"), ("qwerty", "8"), ("8", "9")]
+            .map(|(name, id)| (name.to_owned(), id.to_owned())));
+        let keys = ["semimak", "7", "8", "unknown"].map(str::to_owned);
+        // `8` is the id of qwerty before being the name of another layout
+        assert_eq!(name_keys(keys.iter(), &ids), vec![("semimak".to_owned(), "7".to_owned())]);
+    }
+}
@@ -1,7 +1,7 @@
 use fxhash::FxHashSet;
 use once_cell::sync::Lazy;
 use serenity::model::prelude::ChannelId;
-use crate::util::core::Metric;
+use crate::util::core::{Metric, Row};
 use crate::util::jsons::get_table;
 
 pub const CMINI_CHANNEL: ChannelId = ChannelId(1063291226243207268);
@@ -16,6 +16,18 @@ pub static ADMINS: Lazy<FxHashSet<u64>> = Lazy::new(|| FxHashSet::from_iter([
 
 pub static TABLE: Lazy<[Metric; 4096]> = Lazy::new(|| get_table("./table.json"));
 
+pub const BOARDS: [&str; 5] = ["ortho", "angle", "stagger", "mini", "split"];
+pub const FINGER_NAMES: [&str; 10] = ["LP", "LR", "LM", "LI", "LT", "RT", "RI", "RM", "RR", "RP"];
 pub const FMAP_STANDARD: [u16; 10] = [0, 1, 2, 3, 3, 6, 6, 7, 8, 9];
 pub const FMAP_ANGLE: [u16; 10] = [1, 2, 3, 3, 3, 6, 6, 7, 8, 9];
+/// Genkey numbers the fingers LP to RP without thumbs, then the left and right thumbs
+pub const GENKEY_FINGERS: [u16; 10] = [0, 1, 2, 3, 6, 7, 8, 9, 4, 5];
+pub const THUMB_ROW: Row = 3;
+/// The number row is stored after the thumb row, but shown above the top row
+pub const NUMBER_ROW: Row = 4;
 pub const FREE_CHAR: char = '~';
+pub const STANDARD_KEYS: &str = "abcdefghijklmnopqrstuvwxyz,./;";
+/// Characters allowed in a Discord message
+pub const MESSAGE_LIMIT: usize = 2000;
+/// Attachments larger than this are not downloaded
+pub const MAX_ATTACHMENT_SIZE: u64 = 1 << 20;
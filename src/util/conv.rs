@@ -1,3 +1,4 @@
+use crate::util::core::Position;
 use fxhash::FxHasher;
 use std::hash::Hasher;
 
@@ -8,15 +9,27 @@ pub fn hash_keys(keys: &str) -> u64 {
     hasher.finish()
 }
 
+/// Packing version 1 stores each field of a position in 4 bits, version 2 in 8 bits
+pub const PACKING_V1: u8 = 1;
+pub const PACKING_V2: u8 = 2;
+
+/// Oldest packing version that can store every position without truncating it
+pub fn packing_version<'a>(positions: impl IntoIterator<Item = &'a Position>) -> u8 {
+    match positions.into_iter().all(|(row, col, finger)| *row < 16 && *col < 16 && *finger < 16) {
+        true => PACKING_V1,
+        false => PACKING_V2,
+    }
+}
+
 pub mod layout {
     use crate::util::conv::pos;
     use crate::util::core::Layout;
 
-    pub fn pack(layout: &Layout) -> String {
+    pub fn pack(layout: &Layout, version: u8) -> String {
         let mut layout_packed_ordered: Vec<(String, u32)> = layout.iter().map(|(key, pos)| {
-            let mut packed_keypos = String::with_capacity(4);
+            let mut packed_keypos = String::with_capacity(1 + pos::len(version));
             packed_keypos.push(*key);
-            let packed_pos = pos::pack(pos);
+            let packed_pos = pos::pack(pos, version);
             packed_keypos.push_str(&packed_pos);
             let order = ((pos.0 as u32) << 8) + (pos.1 as u32);
             (packed_keypos, order)
@@ -31,23 +44,39 @@ pub mod layout {
         layout_packed
     }
 
-    pub fn unpack(layout_packed: &str) -> Layout {
+    pub fn unpack(layout_packed: &str, version: u8) -> Layout {
         let mut layout = Layout::default();
         let unpacked_chars: Vec<char> = layout_packed.chars().collect();
+        let len = pos::len(version);
 
-        for start in (0..unpacked_chars.len()).step_by(4) {
+        for start in (0..unpacked_chars.len()).step_by(1 + len) {
             let key = unpacked_chars[start];
-            let mut chunk = String::with_capacity(3);
-            (start + 1 .. start + 4).for_each(|index| {
-                chunk.push(unpacked_chars[index])
-            });
-            let pos = pos::unpack(&chunk);
+            let chunk: String = unpacked_chars[start + 1 .. start + 1 + len].iter().collect();
+            let pos = pos::unpack(&chunk, version);
             layout.insert(key, pos);
         }
         layout
     }
 }
 
+pub mod positions {
+    use crate::util::conv::pos;
+    use crate::util::core::Position;
+
+    pub fn pack(positions: &[Position], version: u8) -> String {
+        let mut positions = positions.to_vec();
+        positions.sort_unstable();
+        positions.iter().map(|position| pos::pack(position, version)).collect()
+    }
+
+    pub fn unpack(packed: &str, version: u8) -> Vec<Position> {
+        let len = pos::len(version);
+        (0..packed.len()).step_by(len)
+            .map(|start| pos::unpack(&packed[start..start + len], version))
+            .collect()
+    }
+}
+
 
 pub mod stats {
     use crate::util::conv::freq;
@@ -120,25 +149,42 @@ mod freq {
 
 
 mod pos {
+    use crate::util::conv::PACKING_V1;
     use crate::util::core::Position;
 
+    /// Length of a packed position
     #[inline]
-    pub fn pack((row, col, finger): &Position) -> String {
-        let mut packed = (u16::from(*row) & 0xf) << 8;
-        packed |= (u16::from(*col) & 0xf) << 4;
-        packed |= finger & 0xf;
-        format!("{:03x}", packed)
+    pub fn len(version: u8) -> usize {
+        match version {
+            PACKING_V1 => 3,
+            _ => 6,
+        }
     }
 
     #[inline]
-    pub fn unpack(packed_str: &str) -> Position {
-        let packed = u16::from_str_radix(packed_str, 16).unwrap();
-        let row = (packed >> 8 & 0xf) as u8;
-        let col = (packed >> 4 & 0xf) as u8;
-        let finger = packed & 0xf;
-        (row, col, finger)
+    pub fn pack((row, col, finger): &Position, version: u8) -> String {
+        match version {
+            PACKING_V1 => {
+                let mut packed = (u16::from(*row) & 0xf) << 8;
+                packed |= (u16::from(*col) & 0xf) << 4;
+                packed |= finger & 0xf;
+                format!("{:03x}", packed)
+            }
+            _ => format!("{:02x}{:02x}{:02x}", row, col, finger & 0xff),
+        }
     }
-}
-
-
 
+    #[inline]
+    pub fn unpack(packed_str: &str, version: u8) -> Position {
+        let packed = u32::from_str_radix(packed_str, 16).unwrap();
+        match version {
+            PACKING_V1 => {
+                let row = (packed >> 8 & 0xf) as u8;
+                let col = (packed >> 4 & 0xf) as u8;
+                let finger = (packed & 0xf) as u16;
+                (row, col, finger)
+            }
+            _ => ((packed >> 16 & 0xff) as u8, (packed >> 8 & 0xff) as u8, (packed & 0xff) as u16),
+        }
+    }
+}
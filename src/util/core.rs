@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::util::consts::ADMINS;
 use crate::util::{conv, Message};
@@ -16,6 +17,8 @@ pub type Col = u8;
 pub type Finger = u16;
 pub type Key = char;
 pub type Position = (Row, Col, Finger);
+/// Stable id of a layout, kept through renames, 0 until the layout is added
+pub type LayoutId = u64;
 
 pub type FxIndexMap<K, V> = IndexMap<K, V, FxBuildHasher>;
 pub type SyncFxMap<K, V> = Arc<RwLock<FxHashMap<K, Arc<V>>>>;
@@ -34,6 +37,7 @@ pub type RawServerCorpora<Gram> = SyncFxMap<String, RawCorpus<Gram>>;
 pub type ServerCorpora<const N: usize> = SyncFxMap<String, Corpus<N>>;
 pub type ServerWordCorpora = SyncFxMap<String, WordCorpus>;
 pub type ServerCachedStats = SyncIndexMap<String, RawCachedStatConfig>;
+pub type ServerLikes = Arc<RwLock<FxHashMap<String, Vec<Like>>>>;
 
 // Trait: Commandable
 // Struct: Command
@@ -42,39 +46,279 @@ pub type DynCommand = Box<dyn Commandable>;
 
 #[derive(Debug, Deserialize)]
 pub struct JsonLayoutConfig {
+    #[serde(default)]
+    pub id: LayoutId,
     pub user: u64,
     pub board: String,
     pub keys: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub free: String,
+    /// Packing version of `keys` and `free`, 1 if absent
+    #[serde(default)]
+    pub packing: Option<u8>,
+    #[serde(default)]
+    pub history: Vec<JsonRevision>,
+    #[serde(default)]
+    pub meta: Metadata,
+}
+
+/// Layout as stored in its own file in `layouts/`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonLayoutFile {
+    pub name: String,
+    pub user: u64,
+    pub board: String,
+    pub keys: FxIndexMap<Key, JsonKey>,
+    #[serde(default)]
+    pub free: Vec<JsonKey>,
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    pub meta: Metadata,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonKey {
+    pub row: Row,
+    pub col: Col,
+    pub finger: String,
+}
+
+/// Like of a layout, timed if it was given after likes started being timed
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(from = "JsonLike", into = "JsonLike")]
+pub struct Like {
+    pub user: u64,
+    /// Unix time of the like
+    pub time: Option<u64>,
+}
+
+impl Like {
+    pub fn new(user: u64) -> Self {
+        Like { user, time: Some(unix_time()) }
+    }
+}
+
+/// Untimed likes are stored as the ID of the user
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum JsonLike {
+    User(u64),
+    Timed { user: u64, time: u64 },
+}
+
+impl From<JsonLike> for Like {
+    fn from(like: JsonLike) -> Self {
+        match like {
+            JsonLike::User(user) => Like { user, time: None },
+            JsonLike::Timed { user, time } => Like { user, time: Some(time) },
+        }
+    }
+}
+
+impl From<Like> for JsonLike {
+    fn from(like: Like) -> Self {
+        match like.time {
+            None => JsonLike::User(like.user),
+            Some(time) => JsonLike::Timed { user: like.user, time },
+        }
+    }
+}
+
+/// What a layout is about, edited with `meta`
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Metadata {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Unix time the layout was added, unknown for the older layouts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+    /// Language the layout is made for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+impl Metadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Metadata::default()
+    }
+}
+
+pub fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+}
+
+/// Earlier state of a layout, saved when it is edited, renamed or reassigned
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision {
+    pub name: String,
+    pub user: u64,
+    pub board: String,
+    pub keys: Layout,
+    /// Unix time of the change that replaced this state
+    pub time: u64,
+    /// User who made the change
+    pub editor: u64,
+}
+
+impl Revision {
+    pub fn from_json(revision: JsonRevision) -> Self {
+        let version = revision.packing.unwrap_or(conv::PACKING_V1);
+        Revision {
+            name: revision.name,
+            user: revision.user,
+            board: revision.board,
+            keys: conv::layout::unpack(&revision.keys, version),
+            time: revision.time,
+            editor: revision.editor,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonRevision {
+    pub name: String,
+    pub user: u64,
+    pub board: String,
+    pub keys: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packing: Option<u8>,
+    pub time: u64,
+    pub editor: u64,
+}
+
+impl JsonRevision {
+    pub fn from_revision(revision: &Revision) -> Self {
+        let version = conv::packing_version(revision.keys.values());
+        JsonRevision {
+            name: revision.name.clone(),
+            user: revision.user,
+            board: revision.board.clone(),
+            keys: conv::layout::pack(&revision.keys, version),
+            packing: (version != conv::PACKING_V1).then_some(version),
+            time: revision.time,
+            editor: revision.editor,
+        }
+    }
 }
 
 pub struct LayoutConfig {
+    pub id: LayoutId,
     pub name: String,
     pub user: u64,
     pub board: String,
     pub keys: Layout,
     pub sum: u64,
+    /// Name with its original case, if it differs from `name`
+    pub display_name: Option<String>,
+    /// Positions without a key
+    pub free: Vec<Position>,
+    /// Earlier states, oldest first
+    pub history: Vec<Revision>,
+    pub meta: Metadata,
 }
 
 impl LayoutConfig {
     pub fn new(name: String, user: u64, board: String, keys: Layout) -> Self {
-        let packed = conv::layout::pack(&keys);
-        let sum = conv::hash_keys(&packed);
-        LayoutConfig {
+        let mut ll = LayoutConfig {
+            id: 0,
             name,
             user,
             board,
             keys,
-            sum,
-        }
+            sum: 0,
+            display_name: None,
+            free: Vec::new(),
+            history: Vec::new(),
+            meta: Metadata::default(),
+        };
+        ll.sum = ll.hash_keys();
+        ll
+    }
+
+    /// Hash of the keys as they are stored, which is what the sum is computed from when loading
+    fn hash_keys(&self) -> u64 {
+        conv::hash_keys(&conv::layout::pack(&self.keys, self.packing_version()))
+    }
+
+    /// Replaces the keys, updating the sum so that the cached stats are recomputed
+    pub fn set_keys(&mut self, keys: Layout) {
+        self.keys = keys;
+        self.sum = self.hash_keys();
+    }
+
+    /// Replaces the free positions, which decide how the keys are packed and so their sum
+    pub fn set_free(&mut self, free: Vec<Position>) {
+        self.free = free;
+        self.sum = self.hash_keys();
+    }
+
+    /// Key of the layout in `likes.json`, `links.json` and `cached_stats.json`
+    pub fn store_key(&self) -> String {
+        self.id.to_string()
+    }
+
+    pub fn title(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Saves the current state before `editor` changes it
+    pub fn push_revision(&mut self, editor: u64) {
+        self.history.push(Revision {
+            name: self.name.clone(),
+            user: self.user,
+            board: self.board.clone(),
+            keys: self.keys.clone(),
+            time: unix_time(),
+            editor,
+        });
+    }
+
+    /// Layout as of revision `rev`, counting from 1 for the oldest state,
+    /// where the revision after the last saved one is the current state
+    pub fn revision(&self, rev: usize) -> Option<LayoutConfig> {
+        let (name, user, board, keys) = match self.history.get(rev.checked_sub(1)?) {
+            Some(revision) => (&revision.name, revision.user, &revision.board, &revision.keys),
+            None if rev == self.history.len() + 1 => (&self.name, self.user, &self.board, &self.keys),
+            None => return None,
+        };
+        let mut ll = LayoutConfig::new(self.name.clone(), user, board.clone(), keys.clone());
+        ll.id = self.id;
+        ll.display_name = Some(format!("{name}@{rev}"));
+        Some(ll)
+    }
+
+    pub fn packing_version(&self) -> u8 {
+        conv::packing_version(self.keys.values().chain(self.free.iter()))
     }
 }
 
 impl Serialize for LayoutConfig {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        let mut map = serializer.serialize_map(Some(3))?;
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("id", &self.id)?;
         map.serialize_entry("user", &self.user)?;
         map.serialize_entry("board", &self.board)?;
-        map.serialize_entry("keys", &conv::layout::pack(&self.keys))?;
+        let version = self.packing_version();
+        map.serialize_entry("keys", &conv::layout::pack(&self.keys, version))?;
+        if let Some(display_name) = &self.display_name {
+            map.serialize_entry("name", display_name)?;
+        }
+        if !self.free.is_empty() {
+            map.serialize_entry("free", &conv::positions::pack(&self.free, version))?;
+        }
+        if version != conv::PACKING_V1 {
+            map.serialize_entry("packing", &version)?;
+        }
+        if !self.history.is_empty() {
+            let history: Vec<JsonRevision> = self.history.iter().map(JsonRevision::from_revision).collect();
+            map.serialize_entry("history", &history)?;
+        }
+        if !self.meta.is_empty() {
+            map.serialize_entry("meta", &self.meta)?;
+        }
         map.end()
     }
 }
@@ -142,6 +386,8 @@ pub enum Metric {
 }
 
 impl Metric {
+    pub const COUNT: usize = 14;
+
     pub fn from_str(s: &str) -> Self {
         match s {
             "sfb" => Metric::Sfb,
@@ -203,6 +449,29 @@ impl Metric {
     }
 }
 
+/// File attached to a reply
+#[derive(Debug)]
+pub struct Attachment {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
+/// Response of a command, with optional file attachments
+#[derive(Debug, Default)]
+pub struct Reply {
+    pub content: String,
+    pub files: Vec<Attachment>,
+}
+
+impl From<String> for Reply {
+    fn from(content: String) -> Self {
+        Reply {
+            content,
+            files: Vec::new(),
+        }
+    }
+}
+
 pub trait Commandable: Send + Sync {
     fn exec(&self, msg: &Message) -> String;
     fn usage<'a>(&self) -> &'a str;
@@ -229,11 +498,27 @@ pub trait Commandable: Send + Sync {
         false
     }
 
-    fn try_exec(&self, msg: &Message) -> String {
+    /// Whether the names of the user are saved to `authors.json` before `exec`,
+    /// so that the layouts of new contributors show who made them
+    fn records_author(&self) -> bool {
+        false
+    }
+
+    /// Whether the attachments of the message are downloaded into `Message::files` before `exec`
+    fn reads_attachments(&self) -> bool {
+        false
+    }
+
+    /// Same as `exec`, overridden by commands that reply with attachments
+    fn exec_reply(&self, msg: &Message) -> Reply {
+        Reply::from(self.exec(msg))
+    }
+
+    fn try_exec(&self, msg: &Message) -> Reply {
         if !self.mods_only() || ADMINS.contains(&msg.id) {
-            self.exec(msg)
+            self.exec_reply(msg)
         } else {
-            "Unauthorized".to_owned()
+            Reply::from("Unauthorized".to_owned())
         }
     }
 }
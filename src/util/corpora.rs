@@ -1,4 +1,5 @@
 use std::fmt::Debug;
+use std::hash::Hash;
 use crate::util::core::{Corpus, Key, RawCorpus, RawServerCorpora, ServerCorpora, ServerWordCorpora, WordCorpus};
 use crate::util::jsons::{get_corpus, read_json};
 use fxhash::FxHashMap;
@@ -47,11 +48,31 @@ pub fn load_corpus<Gram: BorrowCorpus + 'static>(path: &str) -> Arc<RawCorpus<Gr
 
 pub fn ngrams<const N: usize>(id: u64) -> Arc<Corpus<N>>
 where [Key; N]: BorrowCorpus {
-    let user_corpus = get_user_corpus(id);
-    let path = format!("./corpora/{}/{}.json", user_corpus, NGRAMS[N - 1]);
+    corpus_ngrams(&get_user_corpus(id))
+}
+
+pub fn corpus_ngrams<const N: usize>(corpus: &str) -> Arc<Corpus<N>>
+where [Key; N]: BorrowCorpus {
+    let path = format!("./corpora/{}/{}.json", corpus, NGRAMS[N - 1]);
     load_corpus::<[Key; N]>(&path)
 }
 
+/// Merges grams that collapsed into the same key after lowercasing,
+/// sorted by frequency in descending order
+pub fn merge_grams<Gram: Clone + Eq + Hash + Ord>(corpus: &RawCorpus<Gram>) -> Vec<(Gram, u64)> {
+    let mut merged: FxHashMap<&Gram, u64> = FxHashMap::default();
+    for (gram, count) in corpus.iter() {
+        *merged.entry(gram).or_insert(0) += count;
+    }
+    let mut merged: Vec<(Gram, u64)> = merged.into_iter()
+        .map(|(gram, count)| (gram.clone(), count))
+        .collect();
+    merged.sort_by(|(gram0, count0), (gram1, count1)| {
+        count1.cmp(count0).then_with(|| gram0.cmp(gram1))
+    });
+    merged
+}
+
 pub fn words(id: u64) -> Arc<WordCorpus> {
     let user_corpus = get_user_corpus(id);
     let path = format!("./corpora/{}/words.json", user_corpus);
@@ -113,3 +134,10 @@ fn test_glob() {
         }
     }
 }
+
+#[test]
+fn test_merge_grams() {
+    let corpus: Vec<([Key; 1], u64)> = vec![(['e'], 3), (['t'], 4), (['e'], 2), (['a'], 4)];
+    let merged = merge_grams(&corpus);
+    assert_eq!(merged, vec![(['e'], 5), (['a'], 4), (['t'], 4)]);
+}
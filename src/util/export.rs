@@ -0,0 +1,459 @@
+use crate::util::consts::THUMB_ROW;
+use crate::util::core::{Attachment, Col, Key, LayoutConfig};
+use std::fmt::Write;
+
+pub const EXPORT_FORMATS: [&str; 7] = ["xkb", "klc", "keylayout", "qmk", "kanata", "kmonad", "ahk"];
+
+/// Key of an ANSI keyboard that a layout position is typed on
+struct PhysicalKey {
+    qwerty: Key,
+    scancode: u8,
+    xkb: &'static str,
+    mac: u8,
+}
+
+const fn key(qwerty: Key, scancode: u8, xkb: &'static str, mac: u8) -> PhysicalKey {
+    PhysicalKey { qwerty, scancode, xkb, mac }
+}
+
+/// Physical keys by row and column of the layout
+const PHYSICAL_KEYS: [&[PhysicalKey]; 3] = [
+    &[
+        key('q', 0x10, "AD01", 12), key('w', 0x11, "AD02", 13), key('e', 0x12, "AD03", 14),
+        key('r', 0x13, "AD04", 15), key('t', 0x14, "AD05", 17), key('y', 0x15, "AD06", 16),
+        key('u', 0x16, "AD07", 32), key('i', 0x17, "AD08", 34), key('o', 0x18, "AD09", 31),
+        key('p', 0x19, "AD10", 35), key('[', 0x1A, "AD11", 33), key(']', 0x1B, "AD12", 30),
+        key('\\', 0x2B, "BKSL", 42),
+    ],
+    &[
+        key('a', 0x1E, "AC01", 0), key('s', 0x1F, "AC02", 1), key('d', 0x20, "AC03", 2),
+        key('f', 0x21, "AC04", 3), key('g', 0x22, "AC05", 5), key('h', 0x23, "AC06", 4),
+        key('j', 0x24, "AC07", 38), key('k', 0x25, "AC08", 40), key('l', 0x26, "AC09", 37),
+        key(';', 0x27, "AC10", 41), key('\'', 0x28, "AC11", 39),
+    ],
+    &[
+        key('z', 0x2C, "AB01", 6), key('x', 0x2D, "AB02", 7), key('c', 0x2E, "AB03", 8),
+        key('v', 0x2F, "AB04", 9), key('b', 0x30, "AB05", 11), key('n', 0x31, "AB06", 45),
+        key('m', 0x32, "AB07", 46), key(',', 0x33, "AB08", 43), key('.', 0x34, "AB09", 47),
+        key('/', 0x35, "AB10", 44),
+    ],
+];
+
+/// Unshifted and shifted characters of US keyboards
+const US_SHIFT_PAIRS: [(Key, Key); 21] = [
+    ('`', '~'), ('1', '!'), ('2', '@'), ('3', '#'), ('4', '$'), ('5', '%'), ('6', '^'),
+    ('7', '&'), ('8', '*'), ('9', '('), ('0', ')'), ('-', '_'), ('=', '+'), ('[', '{'),
+    (']', '}'), ('\\', '|'), (';', ':'), ('\'', '"'), (',', '<'), ('.', '>'), ('/', '?'),
+];
+
+fn shifted(key: Key) -> Key {
+    match US_SHIFT_PAIRS.iter().find(|(unshifted, _)| *unshifted == key) {
+        Some((_, shifted)) => *shifted,
+        None => key.to_uppercase().next().unwrap_or(key),
+    }
+}
+
+/// Keys of the layout on the physical keys, and the keys that no physical key can type
+struct Remap<'a> {
+    keys: Vec<(&'a PhysicalKey, Option<Key>)>,
+    skipped: Vec<Key>,
+}
+
+impl<'a> Remap<'a> {
+    fn new(ll: &LayoutConfig) -> Remap<'a> {
+        let mut keys: Vec<(&PhysicalKey, Option<Key>)> = PHYSICAL_KEYS.iter()
+            .flat_map(|row| row.iter().map(|physical| (physical, None)))
+            .collect();
+        let mut skipped: Vec<Key> = Vec::new();
+        for (key, (row, col, _)) in ll.keys.iter() {
+            let physical = PHYSICAL_KEYS.get(usize::from(*row))
+                .and_then(|keys| keys.get(usize::from(*col)));
+            match physical {
+                Some(physical) => {
+                    let slot = keys.iter_mut().find(|(other, _)| std::ptr::eq(*other, physical)).unwrap();
+                    slot.1 = Some(*key);
+                }
+                None => skipped.push(*key),
+            }
+        }
+        skipped.sort_unstable();
+        Remap { keys, skipped }
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[(&'a PhysicalKey, Option<Key>)]> {
+        let mut start = 0;
+        PHYSICAL_KEYS.iter().map(move |row| {
+            let keys = &self.keys[start..start + row.len()];
+            start += row.len();
+            keys
+        })
+    }
+}
+
+/// Converts a layout into a keymap file of the given format, also returning the keys it had to leave out
+pub fn export(ll: &LayoutConfig, format: &str) -> Result<(Attachment, Vec<Key>), String> {
+    let remap = Remap::new(ll);
+    let (extension, data) = match format {
+        "xkb" => ("xkb", to_xkb(ll, &remap).into_bytes()),
+        "klc" => ("klc", to_klc(ll, &remap)),
+        "keylayout" => ("keylayout", to_keylayout(ll, &remap).into_bytes()),
+        "qmk" => ("c", to_qmk(ll).into_bytes()),
+        "kanata" => ("kbd", to_kanata(ll, &remap).into_bytes()),
+        "kmonad" => ("kbd", to_kmonad(ll, &remap).into_bytes()),
+        "ahk" => ("ahk", to_ahk(ll, &remap).into_bytes()),
+        _ => return Err(format!("Error: unknown format `{format}`, expected one of: {}", EXPORT_FORMATS.join(", "))),
+    };
+    let attachment = Attachment {
+        filename: format!("{}-{format}.{extension}", ll.name),
+        data,
+    };
+    let skipped = match format {
+        "qmk" => qmk_skipped(ll),
+        _ => remap.skipped,
+    };
+    Ok((attachment, skipped))
+}
+
+fn to_xkb(ll: &LayoutConfig, remap: &Remap) -> String {
+    let keysym = |key: Key| -> String {
+        let name = match key {
+            '`' => "grave", '~' => "asciitilde", '!' => "exclam", '@' => "at", '#' => "numbersign",
+            '$' => "dollar", '%' => "percent", '^' => "asciicircum", '&' => "ampersand",
+            '*' => "asterisk", '(' => "parenleft", ')' => "parenright", '-' => "minus",
+            '_' => "underscore", '=' => "equal", '+' => "plus", '[' => "bracketleft",
+            '{' => "braceleft", ']' => "bracketright", '}' => "braceright", '\\' => "backslash",
+            '|' => "bar", ';' => "semicolon", ':' => "colon", '\'' => "apostrophe",
+            '"' => "quotedbl", ',' => "comma", '<' => "less", '.' => "period", '>' => "greater",
+            '/' => "slash", '?' => "question",
+            key if key.is_ascii_alphanumeric() => return key.to_string(),
+            key => return format!("U{:04X}", u32::from(key)),
+        };
+        name.to_owned()
+    };
+
+    let mut s = format!("// {} for xkb, install into /usr/share/X11/xkb/symbols\n\
+                         default partial alphanumeric_keys\n\
+                         xkb_symbols \"basic\" {{\n    \
+                             include \"us(basic)\"\n    \
+                             name[Group1] = \"{}\";\n\n", ll.name, ll.name);
+    for (physical, key) in remap.keys.iter() {
+        if let Some(key) = key {
+            let _ = writeln!(s, "    key <{}> {{ [ {}, {} ] }};", physical.xkb, keysym(*key), keysym(shifted(*key)));
+        }
+    }
+    s.push_str("};\n");
+    s
+}
+
+fn to_klc(ll: &LayoutConfig, remap: &Remap) -> Vec<u8> {
+    // Keys without their own virtual key keep the one of the physical key
+    let vk = |physical: &PhysicalKey, key: Key| -> String {
+        [key, physical.qwerty].into_iter()
+            .find_map(|key| match key.is_ascii_alphanumeric() {
+                true => Some(key.to_ascii_uppercase().to_string()),
+                false => oem_vk(key).map(str::to_owned),
+            })
+            .unwrap_or_default()
+    };
+    // The short name of a keyboard is at most 8 alphanumeric characters
+    let short_name: String = ll.name.chars().filter(char::is_ascii_alphanumeric).take(8).collect();
+
+    let mut s = format!("KBD\t{short_name}\t\"{}\"\n\n\
+                         COPYRIGHT\t\"(c) cmini\"\n\n\
+                         COMPANY\t\"cmini\"\n\n\
+                         LOCALENAME\t\"en-US\"\n\n\
+                         LOCALEID\t\"00000409\"\n\n\
+                         VERSION\t1.0\n\n\
+                         SHIFTSTATE\n\n\
+                         0\t//Column 4\n\
+                         1\t//Column 5 : Shft\n\n\
+                         LAYOUT\t\t;an extra '@' at the end is a dead key\n\n\
+                         //SC\tVK_\t\tCap\t0\t1\n\
+                         //--\t----\t\t----\t----\t----\n\n", ll.name);
+    for (physical, key) in remap.keys.iter() {
+        let Some(key) = key else {
+            continue;
+        };
+        let cap = u8::from(key.is_alphabetic());
+        let _ = writeln!(s, "{:02x}\t{}\t\t{cap}\t{:04x}\t{:04x}",
+                         physical.scancode, vk(physical, *key), u32::from(*key), u32::from(shifted(*key)));
+    }
+    let _ = write!(s, "\nDESCRIPTIONS\n\n0409\t{}\n\nLANGUAGENAMES\n\n0409\tEnglish (United States)\n\nENDKBD\n", ll.name);
+
+    // Keyboard layout creator reads UTF-16 files with CRLF line endings
+    let mut data = vec![0xFF, 0xFE];
+    s.replace('\n', "\r\n").encode_utf16().for_each(|unit| data.extend(unit.to_le_bytes()));
+    data
+}
+
+fn oem_vk(key: Key) -> Option<&'static str> {
+    let vk = match key {
+        ';' => "OEM_1", '=' => "OEM_PLUS", ',' => "OEM_COMMA", '-' => "OEM_MINUS",
+        '.' => "OEM_PERIOD", '/' => "OEM_2", '`' => "OEM_3", '[' => "OEM_4",
+        '\\' => "OEM_5", ']' => "OEM_6", '\'' => "OEM_7",
+        _ => return None,
+    };
+    Some(vk)
+}
+
+fn to_keylayout(ll: &LayoutConfig, remap: &Remap) -> String {
+    // Keys outside of the layout, so that they still type something
+    const OTHER_KEYS: [(u8, &str, &str); 20] = [
+        (50, "`", "~"), (18, "1", "!"), (19, "2", "@"), (20, "3", "#"), (21, "4", "$"),
+        (23, "5", "%"), (22, "6", "^"), (26, "7", "&"), (28, "8", "*"), (25, "9", "("),
+        (29, "0", ")"), (27, "-", "_"), (24, "=", "+"), (49, " ", " "), (36, "\r", "\r"),
+        (48, "\t", "\t"), (51, "\u{8}", "\u{8}"), (53, "\u{1b}", "\u{1b}"), (76, "\u{3}", "\u{3}"),
+        (117, "\u{7f}", "\u{7f}"),
+    ];
+    let escape = |s: &str| -> String {
+        s.chars()
+            .map(|c| match c {
+                '&' | '<' | '>' | '"' | '\'' => format!("&#x{:04X};", u32::from(c)),
+                c if c == ' ' || c.is_ascii_graphic() || !c.is_control() => c.to_string(),
+                c => format!("&#x{:04X};", u32::from(c)),
+            })
+            .collect()
+    };
+    // Ids of keyboard layouts without a script code must be negative
+    let id = -((ll.sum % 30000) as i64 + 1);
+
+    let mut s = format!("<?xml version=\"1.1\" encoding=\"UTF-8\"?>\n\
+                         <!DOCTYPE keyboard SYSTEM \"file://localhost/System/Library/DTDs/KeyboardLayout.dtd\">\n\
+                         <keyboard group=\"126\" id=\"{id}\" name=\"{}\" maxout=\"1\">\n  \
+                           <layouts>\n    \
+                             <layout first=\"0\" last=\"17\" modifiers=\"Modifiers\" mapSet=\"ANSI\"/>\n  \
+                           </layouts>\n  \
+                           <modifierMap id=\"Modifiers\" defaultIndex=\"0\">\n    \
+                             <keyMapSelect mapIndex=\"0\"><modifier keys=\"command? anyOption? anyControl?\"/></keyMapSelect>\n    \
+                             <keyMapSelect mapIndex=\"1\"><modifier keys=\"anyShift caps? command? anyOption? anyControl?\"/></keyMapSelect>\n    \
+                             <keyMapSelect mapIndex=\"2\"><modifier keys=\"caps command? anyOption? anyControl?\"/></keyMapSelect>\n  \
+                           </modifierMap>\n  \
+                           <keyMapSet id=\"ANSI\">\n", escape(&ll.name));
+    let caps = |key: Key| -> Key {
+        match key.is_alphabetic() {
+            true => shifted(key),
+            false => key,
+        }
+    };
+    for (index, modify) in [(0, (|key| key) as fn(Key) -> Key), (1, shifted), (2, caps)] {
+        let _ = writeln!(s, "    <keyMap index=\"{index}\">");
+        for (physical, key) in remap.keys.iter() {
+            let key = modify(key.unwrap_or(physical.qwerty));
+            let _ = writeln!(s, "      <key code=\"{}\" output=\"{}\"/>", physical.mac, escape(&key.to_string()));
+        }
+        for (code, unshifted, shifted) in OTHER_KEYS.iter() {
+            let output = if index == 1 { shifted } else { unshifted };
+            let _ = writeln!(s, "      <key code=\"{code}\" output=\"{}\"/>", escape(output));
+        }
+        s.push_str("    </keyMap>\n");
+    }
+    s.push_str("  </keyMapSet>\n</keyboard>\n");
+    s
+}
+
+fn to_qmk(ll: &LayoutConfig) -> String {
+    let keycode = |key: Option<Key>| -> String {
+        let Some(key) = key else {
+            return "KC_NO".to_owned();
+        };
+        let keycode = match key {
+            key if key.is_ascii_alphanumeric() => return format!("KC_{}", key.to_ascii_uppercase()),
+            ',' => "KC_COMM", '.' => "KC_DOT", '/' => "KC_SLSH", ';' => "KC_SCLN", '\'' => "KC_QUOT",
+            '[' => "KC_LBRC", ']' => "KC_RBRC", '\\' => "KC_BSLS", '-' => "KC_MINS", '=' => "KC_EQL",
+            '`' => "KC_GRV", '<' => "KC_LT", '>' => "KC_GT", '?' => "KC_QUES", ':' => "KC_COLN",
+            '"' => "KC_DQUO", '_' => "KC_UNDS", '+' => "KC_PLUS", '!' => "KC_EXLM", '(' => "KC_LPRN",
+            ')' => "KC_RPRN",
+            key => return format!("UC(0x{:04X})", u32::from(key)),
+        };
+        keycode.to_owned()
+    };
+    // The layout is placed on the 3x5 grid of each hand, with the thumb key if it has one
+    let grid: Vec<Vec<String>> = (0..3u8)
+        .map(|row| (0..10u8)
+            .map(|col| keycode(ll.keys.iter().find(|(_, pos)| (pos.0, pos.1) == (row, col)).map(|(key, _)| *key)))
+            .collect())
+        .collect();
+    let (thumb_keys, _) = qmk_thumbs(ll);
+    let thumbs: Vec<String> = ["KC_ESC", "KC_SPC", "KC_TAB", "KC_ENT", "KC_BSPC", "KC_DEL"].iter().zip(thumb_keys)
+        .map(|(default, key)| key.map_or(default.to_string(), |key| keycode(Some(key))))
+        .collect();
+
+    let mut s = format!("// {} for QMK, on a split 3x5+3 keyboard\n", ll.name);
+    s.push_str("// Non-ASCII keys need UNICODE_ENABLE = yes in rules.mk\n\
+                #include QMK_KEYBOARD_H\n\n\
+                const uint16_t PROGMEM keymaps[][MATRIX_ROWS][MATRIX_COLS] = {\n    \
+                    [0] = LAYOUT_split_3x5_3(\n");
+    for row in grid.iter() {
+        let _ = writeln!(s, "        {},  {},", row[..5].join(", "), row[5..].join(", "));
+    }
+    let _ = writeln!(s, "                    {},  {}", thumbs[..3].join(", "), thumbs[3..].join(", "));
+    s.push_str("    )\n};\n");
+    s
+}
+
+/// Thumb keys on the three thumb keys of each hand, listed from left to right,
+/// and the thumb keys that don't fit. Each hand keeps its innermost keys.
+fn qmk_thumbs(ll: &LayoutConfig) -> ([Option<Key>; 6], Vec<Key>) {
+    let thumb_keys = |left: bool| -> Vec<(Col, Key)> {
+        let mut keys: Vec<(Col, Key)> = ll.keys.iter()
+            .filter(|(_, (row, col, finger))| *row == THUMB_ROW && (*finger == 4 || (*finger != 5 && *col < 5)) == left)
+            .map(|(key, (_, col, _))| (*col, *key))
+            .collect();
+        // Innermost first
+        keys.sort_unstable_by(|(col0, key0), (col1, key1)| match left {
+            true => col1.cmp(col0).then(key0.cmp(key1)),
+            false => col0.cmp(col1).then(key0.cmp(key1)),
+        });
+        keys
+    };
+    let (left, right) = (thumb_keys(true), thumb_keys(false));
+    // A single thumb key goes on the middle key of its hand, otherwise keys fill the hand from the inside
+    let slots = |keys: &[(Col, Key)], inside_out: [usize; 3]| -> Vec<usize> {
+        match keys.len() {
+            1 => vec![inside_out[1]],
+            _ => inside_out.to_vec(),
+        }
+    };
+    let mut thumbs = [None; 6];
+    for (keys, inside_out) in [(&left, [2, 1, 0]), (&right, [3, 4, 5])] {
+        for (slot, (_, key)) in slots(keys, inside_out).into_iter().zip(keys.iter()) {
+            thumbs[slot] = Some(*key);
+        }
+    }
+    let skipped = left.iter().skip(3).chain(right.iter().skip(3)).map(|(_, key)| *key).collect();
+    (thumbs, skipped)
+}
+
+/// Keys outside of the 3x5 grid of each hand and the thumb keys
+fn qmk_skipped(ll: &LayoutConfig) -> Vec<Key> {
+    let (_, mut skipped) = qmk_thumbs(ll);
+    skipped.extend(ll.keys.iter()
+        .filter(|(_, (row, col, _))| *row > THUMB_ROW || (*row < THUMB_ROW && *col >= 10))
+        .map(|(key, _)| *key));
+    skipped.sort_unstable();
+    skipped
+}
+
+fn to_kanata(ll: &LayoutConfig, remap: &Remap) -> String {
+    let name = |key: Key| -> String {
+        match key {
+            '(' | ')' | '"' | '{' | '}' | '<' | '>' | '?' | ':' | '|' | '~' | '!' | '@' | '#' | '$'
+            | '%' | '^' | '&' | '*' | '_' | '+' => {
+                let unshifted = US_SHIFT_PAIRS.iter().find(|(_, shifted)| *shifted == key).unwrap().0;
+                format!("S-{unshifted}")
+            }
+            key if key.is_ascii_graphic() => key.to_string(),
+            key => format!("(unicode {key})"),
+        }
+    };
+    let mut s = format!(";; {} for kanata\n(defcfg\n  process-unmapped-keys yes\n)\n\n(defsrc", ll.name);
+    for row in remap.rows() {
+        s.push_str("\n ");
+        row.iter().for_each(|(physical, _)| { let _ = write!(s, " {}", physical.qwerty); });
+    }
+    s.push_str("\n)\n\n(deflayer base");
+    for row in remap.rows() {
+        s.push_str("\n ");
+        row.iter().for_each(|(_, key)| { let _ = write!(s, " {}", key.map_or("_".to_owned(), name)); });
+    }
+    s.push_str("\n)\n");
+    s
+}
+
+fn to_kmonad(ll: &LayoutConfig, remap: &Remap) -> String {
+    let name = |key: Key| -> String {
+        match key {
+            '\\' | '"' | '(' | ')' => format!("\\{key}"),
+            key if key.is_ascii_graphic() => key.to_string(),
+            // KMonad can only type other characters through compose sequences
+            _ => "XX".to_owned(),
+        }
+    };
+    let mut s = format!(";; {} for KMonad\n\
+                         (defcfg\n  \
+                           input  (device-file \"/dev/input/by-id/YOUR-KEYBOARD-event-kbd\")\n  \
+                           output (uinput-sink \"KMonad {}\")\n  \
+                           fallthrough true\n\
+                         )\n\n(defsrc", ll.name, ll.name);
+    for row in remap.rows() {
+        s.push_str("\n ");
+        row.iter().for_each(|(physical, _)| { let _ = write!(s, " {}", name(physical.qwerty)); });
+    }
+    s.push_str("\n)\n\n(deflayer base");
+    for row in remap.rows() {
+        s.push_str("\n ");
+        row.iter().for_each(|(_, key)| { let _ = write!(s, " {}", key.map_or("_".to_owned(), name)); });
+    }
+    s.push_str("\n)\n");
+    s
+}
+
+fn to_ahk(ll: &LayoutConfig, remap: &Remap) -> String {
+    let mut s = format!("; {} for AutoHotkey\n#Requires AutoHotkey v2.0\n#SingleInstance Force\n\n", ll.name);
+    for (physical, key) in remap.keys.iter() {
+        let Some(key) = key.filter(|key| *key != physical.qwerty) else {
+            continue;
+        };
+        let scancode = physical.scancode;
+        let _ = match key {
+            ';' | '`' | '{' | '}' => writeln!(s, "SC{scancode:03X}::`{key}"),
+            key if key.is_ascii_graphic() => writeln!(s, "SC{scancode:03X}::{key}"),
+            key => writeln!(s, "SC{scancode:03X}::Send \"{{Text}}{key}\""),
+        };
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::import::{decode, import};
+    use crate::util::layout::parse_matrix;
+    use crate::util::memory::LAYOUTS;
+
+    #[test]
+    fn test_export_klc() {
+        for name in ["qwerty", "colemak-dh"] {
+            let ll = &*LAYOUTS.get(name);
+            let (attachment, skipped) = export(ll, "klc").unwrap();
+            assert!(skipped.is_empty());
+            let content = decode(&attachment.data).unwrap();
+            let imported = import(Some(&attachment.filename), &content, Some(&ll.board)).unwrap();
+            assert_eq!(imported.keys, ll.keys, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_export_text() {
+        let ll = &*LAYOUTS.get("colemak-dh");
+        let text = |format: &str| -> String {
+            let (attachment, skipped) = export(ll, format).unwrap();
+            // The 3x5 grid of QMK has no room for the eleventh column
+            let expected: Vec<Key> = if format == "qmk" { vec!['\''] } else { Vec::new() };
+            assert_eq!(skipped, expected, "{format}");
+            String::from_utf8(attachment.data).unwrap()
+        };
+        // Colemak puts `f` where QWERTY has `e`
+        assert!(text("xkb").contains("    key <AD03> { [ f, F ] };\n"));
+        let keylayout = text("keylayout");
+        assert!(keylayout.contains("<key code=\"14\" output=\"f\"/>") && keylayout.contains("<key code=\"14\" output=\"F\"/>"));
+        assert!(text("qmk").contains("        KC_Q, KC_W, KC_F, KC_P, KC_B,"));
+        for format in ["kanata", "kmonad"] {
+            let text = text(format);
+            assert!(text.contains("(defsrc\n  q w e r t"), "{format}");
+            assert!(text.contains("(deflayer base\n  q w f p b"), "{format}");
+        }
+        let ahk = text("ahk");
+        assert!(ahk.contains("SC012::f\n") && !ahk.contains("SC010::"));
+    }
+
+    #[test]
+    fn test_export_qmk_thumbs() {
+        let (_, keys) = parse_matrix("q w e r t y u i o p\na s d f g h j k l ;\nz x c v b n m , . /\n1 2 3 4    5", Some("ortho")).unwrap();
+        let ll = LayoutConfig::new("thumbs".to_owned(), 0, "ortho".to_owned(), keys);
+        let (attachment, skipped) = export(&ll, "qmk").unwrap();
+        assert_eq!(skipped, vec!['1']);
+        let qmk = String::from_utf8(attachment.data).unwrap();
+        assert!(qmk.contains("KC_2, KC_3, KC_4,  KC_ENT, KC_5, KC_DEL\n"), "{qmk}");
+    }
+}
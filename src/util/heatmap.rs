@@ -0,0 +1,151 @@
+use crate::util::core::{Corpus, Key, LayoutConfig, Position};
+use crate::util::corpora::merge_grams;
+use crate::util::image::{gradient, text_size, Canvas, Color, BLACK, WHITE};
+use crate::util::analyzer::key_coords;
+use fxhash::FxHashMap;
+
+const KEY_SIZE: f64 = 56.0;
+const KEY_PADDING: f64 = 3.0;
+const MARGIN: f64 = 16.0;
+const TITLE_HEIGHT: f64 = 32.0;
+const HAND_GAP: f64 = 0.5;
+const ARC_COUNT: usize = 12;
+
+const BACKGROUND: Color = [49, 51, 56];
+const OUTLINE: Color = [30, 31, 34];
+const ARC: Color = [40, 90, 220];
+const HEAT: [Color; 3] = [[255, 255, 204], [253, 141, 60], [189, 0, 38]];
+
+/// Renders the layout as a PNG, shading each key by its monogram frequency,
+/// with the most frequent same finger bigrams drawn as arcs if `bigrams` is given
+pub fn render(ll: &LayoutConfig, monograms: &Corpus<1>, bigrams: Option<&Corpus<2>>) -> Vec<u8> {
+    let monograms = merge_grams(monograms);
+    let total = monograms.iter().map(|(_, count)| count).sum::<u64>() as f64;
+    let freqs: FxHashMap<Key, f64> = monograms.into_iter()
+        .map(|([key], count)| (key, count as f64 / total))
+        .collect();
+    let max_freq = ll.keys.keys()
+        .filter_map(|key| freqs.get(key))
+        .fold(f64::EPSILON, |max, freq| max.max(*freq));
+
+    // Keys left of the first column or above the top row, like the number row, shift the layout
+    let offset = ll.keys.values()
+        .map(|pos| key_coords(&ll.board, pos))
+        .fold((0.0, 0.0), |(left, top): (f64, f64), (x, y)| (left.min(x), top.min(y)));
+    let (right, bottom) = ll.keys.values()
+        .map(|pos| key_origin(&ll.board, pos, offset))
+        .fold((0.0, 0.0), |(right, bottom): (f64, f64), (x, y)| {
+            (right.max(x + KEY_SIZE), bottom.max(y + KEY_SIZE))
+        });
+    let width = (right + MARGIN).max(2.0 * MARGIN + f64::from(text_size(&ll.name, 2).0));
+    let mut canvas = Canvas::new(width as u32, (bottom + MARGIN) as u32, BACKGROUND);
+    canvas.draw_text(MARGIN as i64, MARGIN as i64, &ll.name, 2, WHITE);
+
+    let size = (KEY_SIZE - 2.0 * KEY_PADDING) as u32;
+    for (key, pos) in ll.keys.iter() {
+        let (x, y) = key_origin(&ll.board, pos, offset);
+        let (x, y) = ((x + KEY_PADDING) as i64, (y + KEY_PADDING) as i64);
+        let freq = freqs.get(key).copied().unwrap_or(0.0);
+        canvas.fill_rect(x, y, size, size, gradient(&HEAT, freq / max_freq));
+        canvas.draw_rect(x, y, size, size, OUTLINE);
+    }
+
+    if let Some(bigrams) = bigrams {
+        let arcs = get_sfb_arcs(ll, bigrams);
+        let max_count = arcs.first().map(|(_, count)| *count).unwrap_or(1) as f64;
+        for ((key0, key1), count) in arcs.iter() {
+            let start = key_center(&ll.board, &ll.keys[key0], offset);
+            let end = key_center(&ll.board, &ll.keys[key1], offset);
+            // Bend the arc sideways so that arcs between the same columns don't overlap labels
+            let control = (
+                (start.0 + end.0) / 2.0 + (end.1 - start.1) * 0.3,
+                (start.1 + end.1) / 2.0 - (end.0 - start.0) * 0.3,
+            );
+            let thickness = 2.0 + 6.0 * *count as f64 / max_count;
+            canvas.draw_curve(start, control, end, thickness, ARC);
+        }
+    }
+
+    for (key, pos) in ll.keys.iter() {
+        let (cx, cy) = key_center(&ll.board, pos, offset);
+        let freq = freqs.get(key).copied().unwrap_or(0.0);
+        let color = if freq / max_freq > 0.6 { WHITE } else { BLACK };
+        canvas.draw_text_centered(cx, cy - 6.0, &key.to_string(), 3, color);
+        canvas.draw_text_centered(cx, cy + 16.0, &format!("{:.1}", freq * 100.0), 1, color);
+    }
+
+    canvas.encode_png()
+}
+
+fn key_origin(board: &str, pos: &Position, (left, top): (f64, f64)) -> (f64, f64) {
+    let (x, y) = key_coords(board, pos);
+    let (x, y) = (x - left, y - top);
+    let x = if pos.1 >= 5 { x + HAND_GAP } else { x };
+    (MARGIN + x * KEY_SIZE, MARGIN + TITLE_HEIGHT + y * KEY_SIZE)
+}
+
+fn key_center(board: &str, pos: &Position, offset: (f64, f64)) -> (f64, f64) {
+    let (x, y) = key_origin(board, pos, offset);
+    (x + KEY_SIZE / 2.0, y + KEY_SIZE / 2.0)
+}
+
+/// Most frequent same finger bigrams, counting both directions together
+fn get_sfb_arcs(ll: &LayoutConfig, bigrams: &Corpus<2>) -> Vec<((Key, Key), u64)> {
+    let mut arcs: FxHashMap<(Key, Key), u64> = FxHashMap::default();
+    for ([gram0, gram1], count) in bigrams.iter() {
+        if gram0 == gram1 {
+            continue;
+        }
+        let (Some(pos0), Some(pos1)) = (ll.keys.get(gram0), ll.keys.get(gram1)) else {
+            continue;
+        };
+        if pos0.2 == pos1.2 {
+            let pair = if gram0 < gram1 { (*gram0, *gram1) } else { (*gram1, *gram0) };
+            *arcs.entry(pair).or_insert(0) += count;
+        }
+    }
+    let mut arcs: Vec<((Key, Key), u64)> = arcs.into_iter().collect();
+    arcs.sort_by(|(pair0, count0), (pair1, count1)| count1.cmp(count0).then_with(|| pair0.cmp(pair1)));
+    arcs.truncate(ARC_COUNT);
+    arcs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::analyzer::SPLIT_GAP;
+    use crate::util::consts::{NUMBER_ROW, THUMB_ROW};
+    use crate::util::corpora::corpus_ngrams;
+    use crate::util::layout::parse_matrix;
+
+    #[test]
+    fn test_key_coords() {
+        let rows = [NUMBER_ROW, 0, 1, 2, THUMB_ROW].map(|row| (row, 3, 3));
+        let xs = |board: &str| rows.map(|pos| key_coords(board, &pos).0);
+        assert_eq!(xs("ortho"), [3.0, 3.0, 3.0, 3.0, 3.0]);
+        assert_eq!(xs("mini"), [3.0, 3.0, 3.0, 3.0, 3.0]);
+        assert_eq!(xs("angle"), [3.0, 3.0, 3.0, 3.5, 3.0]);
+        assert_eq!(xs("stagger"), [2.5, 3.0, 3.25, 3.75, 3.0]);
+        assert_eq!(xs("split"), [3.0, 3.0, 3.0, 3.0, 3.0]);
+        assert_eq!(key_coords("split", &(0, 5, 6)).0, 5.0 + SPLIT_GAP);
+        assert_eq!(rows.map(|pos| key_coords("ortho", &pos).1), [-1.0, 0.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_render() {
+        let monograms = corpus_ngrams::<1>("mt-quotes");
+        let bigrams = corpus_ngrams::<2>("mt-quotes");
+        let (_, keys) = parse_matrix("q w e r t y u i o p\na s d f g h j k l ;\nz x c v b n m , . /", Some("ortho")).unwrap();
+        for (board, extra) in [("ortho", 0.0), ("angle", 0.5), ("stagger", 0.75), ("split", SPLIT_GAP)] {
+            let ll = LayoutConfig::new("heatmap".to_owned(), 0, board.to_owned(), keys.clone());
+            let png = render(&ll, &monograms, Some(&bigrams));
+            assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+            // Ten keys, the hand gap and the stagger of the bottom row or the split across, a title and three rows down
+            let reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+            let width = 2.0 * MARGIN + (10.0 + HAND_GAP + extra) * KEY_SIZE;
+            let height = 2.0 * MARGIN + TITLE_HEIGHT + 3.0 * KEY_SIZE;
+            assert_eq!((reader.info().width, reader.info().height), (width as u32, height as u32), "{board}");
+        }
+    }
+}
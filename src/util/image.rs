@@ -0,0 +1,254 @@
+pub type Color = [u8; 3];
+
+pub const BLACK: Color = [0, 0, 0];
+pub const WHITE: Color = [255, 255, 255];
+
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+
+/// RGB image that can be encoded as a PNG
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Color) -> Self {
+        let pixels = background.repeat((width * height) as usize);
+        Canvas {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Sets a pixel, ignoring pixels outside the canvas
+    pub fn set(&mut self, x: i64, y: i64, color: Color) {
+        if x < 0 || y < 0 || x >= i64::from(self.width) || y >= i64::from(self.height) {
+            return;
+        }
+        let index = (y as usize * self.width as usize + x as usize) * 3;
+        self.pixels[index..index + 3].copy_from_slice(&color);
+    }
+
+    pub fn fill_rect(&mut self, x: i64, y: i64, width: u32, height: u32, color: Color) {
+        for dy in 0..i64::from(height) {
+            for dx in 0..i64::from(width) {
+                self.set(x + dx, y + dy, color);
+            }
+        }
+    }
+
+    pub fn draw_rect(&mut self, x: i64, y: i64, width: u32, height: u32, color: Color) {
+        let (right, bottom) = (x + i64::from(width) - 1, y + i64::from(height) - 1);
+        for dx in x..=right {
+            self.set(dx, y, color);
+            self.set(dx, bottom, color);
+        }
+        for dy in y..=bottom {
+            self.set(x, dy, color);
+            self.set(right, dy, color);
+        }
+    }
+
+    pub fn fill_circle(&mut self, cx: f64, cy: f64, radius: f64, color: Color) {
+        let reach = radius.ceil() as i64;
+        let (cx_i, cy_i) = (cx.round() as i64, cy.round() as i64);
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let (x, y) = (cx_i + dx, cy_i + dy);
+                if (x as f64 - cx).powi(2) + (y as f64 - cy).powi(2) <= radius * radius {
+                    self.set(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// Draws a quadratic bezier curve from `start` to `end`, bent towards `control`
+    pub fn draw_curve(&mut self, start: (f64, f64), control: (f64, f64), end: (f64, f64),
+                      thickness: f64, color: Color) {
+        let length = (end.0 - start.0).hypot(end.1 - start.1) + (control.0 - start.0).hypot(control.1 - start.1);
+        let steps = (length.ceil() as usize).max(1) * 2;
+        for step in 0..=steps {
+            let t = step as f64 / steps as f64;
+            let u = 1.0 - t;
+            let x = u * u * start.0 + 2.0 * u * t * control.0 + t * t * end.0;
+            let y = u * u * start.1 + 2.0 * u * t * control.1 + t * t * end.1;
+            self.fill_circle(x, y, thickness / 2.0, color);
+        }
+    }
+
+    /// Draws text with the built-in 5x7 font, with `(x, y)` as the top left corner
+    pub fn draw_text(&mut self, x: i64, y: i64, text: &str, scale: u32, color: Color) {
+        let advance = i64::from((GLYPH_WIDTH + 1) * scale);
+        for (index, c) in text.chars().enumerate() {
+            let left = x + index as i64 * advance;
+            for (row, bits) in font::glyph(c).iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits >> (GLYPH_WIDTH - 1 - col) & 1 == 0 {
+                        continue;
+                    }
+                    let px = left + i64::from(col * scale);
+                    let py = y + row as i64 * i64::from(scale);
+                    self.fill_rect(px, py, scale, scale, color);
+                }
+            }
+        }
+    }
+
+    /// Draws text centered on `(cx, cy)`
+    pub fn draw_text_centered(&mut self, cx: f64, cy: f64, text: &str, scale: u32, color: Color) {
+        let (width, height) = text_size(text, scale);
+        let x = (cx - f64::from(width) / 2.0).round() as i64;
+        let y = (cy - f64::from(height) / 2.0).round() as i64;
+        self.draw_text(x, y, text, scale, color);
+    }
+
+    pub fn encode_png(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut data, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            // Writing into a Vec never fails
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&self.pixels).unwrap();
+        }
+        data
+    }
+}
+
+pub fn text_size(text: &str, scale: u32) -> (u32, u32) {
+    let count = text.chars().count() as u32;
+    let width = (count * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale;
+    (width, GLYPH_HEIGHT * scale)
+}
+
+/// Linear interpolation between colors, through every stop in `stops`
+pub fn gradient(stops: &[Color], t: f64) -> Color {
+    let t = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+    let index = (t.floor() as usize).min(stops.len() - 2);
+    let t = t - index as f64;
+    let (from, to) = (stops[index], stops[index + 1]);
+    [0, 1, 2].map(|channel| {
+        (f64::from(from[channel]) * (1.0 - t) + f64::from(to[channel]) * t).round() as u8
+    })
+}
+
+mod font {
+    /// 5x7 glyph, one byte per row with the leftmost pixel as bit 4
+    pub fn glyph(c: char) -> [u8; 7] {
+        match c.to_ascii_lowercase() {
+            ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            'a' => [0x00, 0x00, 0x0e, 0x01, 0x0f, 0x11, 0x0f],
+            'b' => [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1e],
+            'c' => [0x00, 0x00, 0x0e, 0x10, 0x10, 0x11, 0x0e],
+            'd' => [0x01, 0x01, 0x0d, 0x13, 0x11, 0x11, 0x0f],
+            'e' => [0x00, 0x00, 0x0e, 0x11, 0x1f, 0x10, 0x0e],
+            'f' => [0x06, 0x09, 0x08, 0x1c, 0x08, 0x08, 0x08],
+            'g' => [0x00, 0x0f, 0x11, 0x11, 0x0f, 0x01, 0x0e],
+            'h' => [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11],
+            'i' => [0x04, 0x00, 0x0c, 0x04, 0x04, 0x04, 0x0e],
+            'j' => [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0c],
+            'k' => [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12],
+            'l' => [0x0c, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+            'm' => [0x00, 0x00, 0x1a, 0x15, 0x15, 0x11, 0x11],
+            'n' => [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11],
+            'o' => [0x00, 0x00, 0x0e, 0x11, 0x11, 0x11, 0x0e],
+            'p' => [0x00, 0x00, 0x1e, 0x11, 0x1e, 0x10, 0x10],
+            'q' => [0x00, 0x00, 0x0d, 0x13, 0x0f, 0x01, 0x01],
+            'r' => [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10],
+            's' => [0x00, 0x00, 0x0e, 0x10, 0x0e, 0x01, 0x1e],
+            't' => [0x08, 0x08, 0x1c, 0x08, 0x08, 0x09, 0x06],
+            'u' => [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0d],
+            'v' => [0x00, 0x00, 0x11, 0x11, 0x11, 0x0a, 0x04],
+            'w' => [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0a],
+            'x' => [0x00, 0x00, 0x11, 0x0a, 0x04, 0x0a, 0x11],
+            'y' => [0x00, 0x00, 0x11, 0x11, 0x0f, 0x01, 0x0e],
+            'z' => [0x00, 0x00, 0x1f, 0x02, 0x04, 0x08, 0x1f],
+            '0' => [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+            '1' => [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+            '2' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+            '3' => [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+            '4' => [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+            '5' => [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+            '6' => [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+            '7' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+            '8' => [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+            '9' => [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+            '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c],
+            ',' => [0x00, 0x00, 0x00, 0x00, 0x0c, 0x04, 0x08],
+            ';' => [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x04, 0x08],
+            ':' => [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00],
+            '\'' => [0x0c, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+            '"' => [0x0a, 0x0a, 0x0a, 0x00, 0x00, 0x00, 0x00],
+            '`' => [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00],
+            '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+            '\\' => [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00],
+            '|' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+            '-' => [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00],
+            '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1f],
+            '=' => [0x00, 0x00, 0x1f, 0x00, 0x1f, 0x00, 0x00],
+            '+' => [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00],
+            '*' => [0x00, 0x04, 0x15, 0x0e, 0x15, 0x04, 0x00],
+            '[' => [0x0e, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0e],
+            ']' => [0x0e, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0e],
+            '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+            ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+            '{' => [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02],
+            '}' => [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08],
+            '<' => [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02],
+            '>' => [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08],
+            '?' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+            '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+            '~' => [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00],
+            '^' => [0x04, 0x0a, 0x11, 0x00, 0x00, 0x00, 0x00],
+            '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+            '#' => [0x0a, 0x0a, 0x1f, 0x0a, 0x1f, 0x0a, 0x0a],
+            '&' => [0x0c, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0d],
+            '@' => [0x0e, 0x11, 0x01, 0x0d, 0x15, 0x15, 0x0e],
+            '$' => [0x04, 0x0f, 0x14, 0x0e, 0x05, 0x1e, 0x04],
+            // Box for characters without a glyph
+            _ => [0x1f, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1f],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canvas() {
+        let mut canvas = Canvas::new(20, 10, BLACK);
+        canvas.set(-1, 0, WHITE);
+        canvas.set(20, 9, WHITE);
+        assert!(canvas.pixels.iter().all(|channel| *channel == 0));
+        canvas.fill_rect(18, 8, 5, 5, WHITE);
+        assert_eq!(canvas.pixels.iter().filter(|channel| **channel == 255).count(), 4 * 3);
+
+        let png = canvas.encode_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (20, 10));
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(text_size("", 2), (0, 14));
+        assert_eq!(text_size("ab", 1), (11, 7));
+        assert_eq!(text_size("ab", 3), (33, 21));
+
+        let mut canvas = Canvas::new(11, 7, BLACK);
+        canvas.draw_text(0, 0, "l", 1, WHITE);
+        let lit = |x: usize, y: usize| canvas.pixels[(y * 11 + x) * 3] == 255;
+        assert!(lit(2, 3) && !lit(0, 3) && !lit(8, 3));
+        assert_ne!(font::glyph('A'), font::glyph('?'));
+        assert_eq!(font::glyph('a'), font::glyph('A'));
+
+        assert_eq!(gradient(&[BLACK, WHITE], 0.0), BLACK);
+        assert_eq!(gradient(&[BLACK, WHITE], 2.0), WHITE);
+        assert_eq!(gradient(&[BLACK, [200, 100, 0], WHITE], 0.5), [200, 100, 0]);
+    }
+}
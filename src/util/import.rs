@@ -0,0 +1,447 @@
+use crate::util::consts::{FREE_CHAR, GENKEY_FINGERS};
+use crate::util::core::{JsonLayoutFile, Key, Layout};
+use crate::util::layout::{parse_matrix, place_keys};
+use crate::util::jsons::write_json;
+use crate::util::{layout, layout_files};
+use crate::util::memory::LAYOUTS;
+use fxhash::FxHashMap;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Boards that an imported layout can be placed on
+pub const IMPORT_BOARDS: [&str; 3] = ["ortho", "stagger", "angle"];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Rows of keys separated by spaces, as in `add` and Oxeylyzer
+    Matrix,
+    /// Name, rows of keys, then rows of fingers
+    Genkey,
+    /// keyboard-layout-editor raw data
+    Kle,
+    /// Windows keyboard layout creator source
+    Klc,
+    /// Files in `layouts/`
+    Json,
+}
+
+impl Format {
+    pub fn from_extension(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "kb" => Some(Format::Matrix),
+            "genkey" => Some(Format::Genkey),
+            "klc" => Some(Format::Klc),
+            // keyboard-layout-editor and our own files are both JSON, told apart by their content
+            _ => None,
+        }
+    }
+
+    pub fn detect(content: &str) -> Self {
+        let trimmed = content.trim_start();
+        if trimmed.starts_with('[') {
+            return Format::Kle;
+        }
+        if trimmed.starts_with('{') {
+            return Format::Json;
+        }
+        if content.lines().any(|line| line.trim_start().starts_with("KBD")) && content.contains("LAYOUT") {
+            return Format::Klc;
+        }
+        if is_genkey(content) {
+            return Format::Genkey;
+        }
+        Format::Matrix
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Matrix => "matrix",
+            Format::Genkey => "genkey",
+            Format::Kle => "keyboard-layout-editor",
+            Format::Klc => "klc",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// Layout read from another format, before it is named and added
+#[derive(Debug)]
+pub struct Imported {
+    pub format: Format,
+    /// Name given by the file itself, if any
+    pub name: Option<String>,
+    pub user: Option<u64>,
+    pub board: String,
+    pub keys: Layout,
+}
+
+/// Decodes a file as UTF-8, or as UTF-16 if it starts with a byte order mark like `.klc` files do
+pub fn decode(data: &[u8]) -> Result<String, String> {
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| -> Result<String, String> {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| from_bytes([pair[0], pair[1]])).collect();
+        String::from_utf16(&units).map_err(|_| "Error: the file is not valid UTF-16".to_owned())
+    };
+    match data {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] | rest => String::from_utf8(rest.to_vec())
+            .map_err(|_| "Error: the file is not valid UTF-8".to_owned()),
+    }
+}
+
+/// Reads a layout, detecting its format from the file name if given, otherwise from its content.
+/// `board` overrides the board of every format except our own JSON files.
+pub fn import(filename: Option<&str>, content: &str, board: Option<&str>) -> Result<Imported, String> {
+    if let Some(board) = board.filter(|board| !IMPORT_BOARDS.contains(board)) {
+        return Err(format!("Error: cannot import to board `{board}`, expected one of: {}",
+                           IMPORT_BOARDS.join(", ")));
+    }
+    let format = filename.and_then(Format::from_extension).unwrap_or_else(|| Format::detect(content));
+    let mut imported = match format {
+        Format::Matrix => import_matrix(content, board),
+        Format::Genkey => import_genkey(content, board),
+        Format::Kle => import_kle(content, board),
+        Format::Klc => import_klc(content, board),
+        Format::Json => import_json(content),
+    }?;
+    if imported.keys.is_empty() {
+        return Err(format!("Error: no keys found in the {} layout", format.name()));
+    }
+    let mut keys = Layout::default();
+    let mut originals: FxHashMap<Key, Key> = FxHashMap::default();
+    for (key, pos) in imported.keys {
+        let lower = key.to_lowercase().next().unwrap_or(key);
+        if let Some(other) = originals.insert(lower, key) {
+            return Err(format!("Error: `{other}` and `{key}` are both `{lower}` in lowercase"));
+        }
+        keys.insert(lower, pos);
+    }
+    imported.keys = keys;
+    Ok(imported)
+}
+
+fn import_matrix(content: &str, board: Option<&str>) -> Result<Imported, String> {
+    let content = content.trim_matches('\n').to_lowercase();
+    let (board, keys) = match board {
+        None => parse_matrix(&content, None)?,
+        Some(board) => {
+            let rows: Vec<Vec<Key>> = content.lines()
+                .map(|row| row.chars().filter(|c| !c.is_whitespace()).collect())
+                .collect();
+            (board.to_owned(), place_keys(&rows, board)?)
+        }
+    };
+    Ok(Imported {
+        format: Format::Matrix,
+        name: None,
+        user: None,
+        board,
+        keys,
+    })
+}
+
+/// Genkey layouts have as many rows of fingers as rows of keys, after an optional name
+fn is_genkey(content: &str) -> bool {
+    let lines: Vec<&str> = content.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    let rows = lines.len() / 2;
+    rows > 0 && lines[lines.len() - rows..].iter()
+        .all(|line| line.split_whitespace().all(|finger| finger.parse::<u8>().is_ok()))
+}
+
+fn import_genkey(content: &str, board: Option<&str>) -> Result<Imported, String> {
+    let mut lines: Vec<&str> = content.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    let name = match lines.len() % 2 {
+        1 => Some(lines.remove(0).to_owned()),
+        _ => None,
+    };
+    let (key_rows, finger_rows) = lines.split_at(lines.len() / 2);
+
+    let mut keys = Layout::default();
+    for (row, (key_row, finger_row)) in key_rows.iter().zip(finger_rows).enumerate() {
+        let row_keys: Vec<&str> = key_row.split_whitespace().collect();
+        let row_fingers: Vec<&str> = finger_row.split_whitespace().collect();
+        if row_keys.len() != row_fingers.len() {
+            return Err(format!("Error: row {} has {} keys but {} fingers", row + 1, row_keys.len(), row_fingers.len()));
+        }
+        for (col, (key, finger)) in row_keys.iter().zip(row_fingers).enumerate() {
+            let mut chars = key.chars();
+            let (Some(key), None) = (chars.next(), chars.next()) else {
+                return Err(format!("Error: expected a single character, got `{key}`"));
+            };
+            let finger = finger.parse::<usize>().ok()
+                .and_then(|finger| GENKEY_FINGERS.get(finger))
+                .ok_or_else(|| format!("Error: `{finger}` is not a genkey finger"))?;
+            if keys.insert(key, (row as u8, col as u8, *finger)).is_some() {
+                return Err(format!("Error: `{key}` is defined twice"));
+            }
+        }
+    }
+    Ok(Imported {
+        format: Format::Genkey,
+        name,
+        user: None,
+        board: board.unwrap_or("ortho").to_owned(),
+        keys,
+    })
+}
+
+fn import_kle(content: &str, board: Option<&str>) -> Result<Imported, String> {
+    let json: Value = serde_json::from_str(content)
+        .map_err(|err| format!("Error: invalid keyboard-layout-editor JSON: {err}"))?;
+    let Some(rows) = json.as_array() else {
+        return Err("Error: keyboard-layout-editor layouts are a list of rows".to_owned());
+    };
+
+    let mut name = None;
+    // Only rows with letters are part of the layout, the number and modifier rows are skipped
+    let mut key_rows: Vec<(f64, Vec<Key>)> = Vec::new();
+    for row in rows {
+        let Some(items) = row.as_array() else {
+            // The first item may be the metadata of the keyboard
+            name = row.get("name").and_then(Value::as_str).map(str::to_owned);
+            continue;
+        };
+        let mut x = 0.0;
+        let mut width = 1.0;
+        let mut first_x = None;
+        let mut keys: Vec<Key> = Vec::new();
+        for item in items {
+            if let Some(props) = item.as_object() {
+                x += props.get("x").and_then(Value::as_f64).unwrap_or(0.0);
+                width = props.get("w").and_then(Value::as_f64).unwrap_or(width);
+                continue;
+            }
+            if let Some(key) = item.as_str().and_then(kle_key) {
+                first_x.get_or_insert(x);
+                keys.push(key);
+            }
+            x += width;
+            width = 1.0;
+        }
+        if keys.iter().any(|key| key.is_alphabetic()) {
+            key_rows.push((first_x.unwrap_or(0.0), keys));
+        }
+    }
+    if key_rows.len() > 3 {
+        return Err(format!("Error: expected at most 3 rows with letters, got {}", key_rows.len()));
+    }
+
+    let board = board.map(str::to_owned).unwrap_or_else(|| {
+        let offsets: Vec<f64> = key_rows.iter().map(|(x, _)| *x).collect();
+        match offsets.windows(2).all(|pair| (pair[0] - pair[1]).abs() < 0.01) {
+            true => "ortho".to_owned(),
+            false => "stagger".to_owned(),
+        }
+    });
+    let rows: Vec<Vec<Key>> = key_rows.into_iter().map(|(_, keys)| keys).collect();
+    Ok(Imported {
+        format: Format::Kle,
+        name,
+        user: None,
+        keys: place_keys(&rows, &board)?,
+        board,
+    })
+}
+
+/// The unshifted legend of a key, which is the last one in keyboard-layout-editor labels
+fn kle_key(label: &str) -> Option<Key> {
+    label.split('\n')
+        .rfind(|legend| legend.chars().count() == 1)
+        .and_then(|legend| legend.chars().next())
+        .filter(|key| !key.is_whitespace())
+}
+
+fn import_klc(content: &str, board: Option<&str>) -> Result<Imported, String> {
+    const SECTIONS: [&str; 7] = ["SHIFTSTATE", "LIGATURE", "DEADKEY", "KEYNAME", "KEYNAME_EXT", "DESCRIPTIONS", "ENDKBD"];
+    let mut name = None;
+    let mut in_layout = false;
+    let mut rows: Vec<Vec<(u8, Key)>> = vec![Vec::new(); 3];
+
+    for line in content.lines() {
+        // Comments start with `//` anywhere in the line
+        let line = line.split("//").next().unwrap_or("").trim();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.first() {
+            None => continue,
+            Some(&"KBD") => name = words.get(1).map(|name| name.to_string()),
+            Some(&"LAYOUT") => in_layout = true,
+            Some(word) if SECTIONS.contains(word) => in_layout = false,
+            Some(scancode) if in_layout => {
+                let Ok(scancode) = u8::from_str_radix(scancode, 16) else {
+                    continue;
+                };
+                let (Some((row, col)), Some(key)) = (klc_position(scancode), words.get(3).and_then(|key| klc_key(key))) else {
+                    continue;
+                };
+                rows[row].push((col, key));
+            }
+            _ => (),
+        }
+    }
+
+    let rows: Vec<Vec<Key>> = rows.into_iter()
+        .map(|row| {
+            let mut keys = vec![FREE_CHAR; row.iter().map(|(col, _)| usize::from(*col) + 1).max().unwrap_or(0)];
+            row.into_iter().for_each(|(col, key)| keys[usize::from(col)] = key);
+            keys
+        })
+        .collect();
+    let board = board.unwrap_or("stagger").to_owned();
+    Ok(Imported {
+        format: Format::Klc,
+        name,
+        user: None,
+        keys: place_keys(&rows, &board)?,
+        board,
+    })
+}
+
+/// Row and column of the letter keys by their scancode
+fn klc_position(scancode: u8) -> Option<(usize, u8)> {
+    match scancode {
+        0x10..=0x1B => Some((0, scancode - 0x10)),
+        0x2B => Some((0, 12)),
+        0x1E..=0x28 => Some((1, scancode - 0x1E)),
+        0x2C..=0x35 => Some((2, scancode - 0x2C)),
+        _ => None,
+    }
+}
+
+/// Characters are either written as is or as 4 hex digits, and dead keys end with `@`
+fn klc_key(word: &str) -> Option<Key> {
+    let word = word.trim_end_matches('@');
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(key), None) => Some(key),
+        _ if word.len() == 4 => u32::from_str_radix(word, 16).ok().and_then(char::from_u32),
+        _ => None,
+    }
+}
+
+fn import_json(content: &str) -> Result<Imported, String> {
+    let json: JsonLayoutFile = serde_json::from_str(content)
+        .map_err(|err| format!("Error: invalid layout JSON: {err}"))?;
+    let ll = layout_files::from_file(&json.name, &json)?;
+    Ok(Imported {
+        format: Format::Json,
+        name: Some(json.name),
+        user: Some(json.user),
+        board: ll.board,
+        keys: ll.keys,
+    })
+}
+
+/// Name of an imported layout, from the layout itself or else from the file name
+pub fn get_name(imported: &Imported, filename: Option<&str>) -> Option<String> {
+    let stem = filename
+        .and_then(|filename| Path::new(filename).file_stem()?.to_str());
+    let name = imported.name.as_deref().or(stem)?;
+    Some(name.split_whitespace().collect::<Vec<_>>().join("-").to_lowercase())
+}
+
+/// Imports every layout file in `args`, reading directories one level deep.
+/// Layouts are owned by the user given with `--owner <id>`, unless their file names one.
+pub fn import_main(args: &[String]) {
+    let (owner, paths) = match args {
+        [flag, id, paths @ ..] if flag == "--owner" => match id.parse::<u64>() {
+            Ok(id) => (Some(id), paths),
+            Err(_) => {
+                println!("Error: `{id}` is not a user ID");
+                std::process::exit(1);
+            }
+        },
+        paths => (None, paths),
+    };
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in paths.iter().map(PathBuf::from) {
+        match std::fs::read_dir(&path) {
+            Ok(entries) => files.extend(entries.filter_map(|entry| Some(entry.ok()?.path()))),
+            Err(_) => files.push(path),
+        }
+    }
+    files.sort();
+
+    let mut count = 0;
+    for path in files.iter() {
+        let filename = path.file_name().and_then(|filename| filename.to_str());
+        let imported = std::fs::read(path)
+            .map_err(|err| format!("Error: {err}"))
+            .and_then(|data| decode(&data))
+            .and_then(|content| import(filename, &content, None));
+        let result = imported.and_then(|imported| {
+            let name = get_name(&imported, filename).ok_or("Error: the layout has no name")?;
+            let user = imported.user.or(owner).ok_or("Error: the layout has no owner, give one with `--owner <id>`")?;
+            layout::create(&name, user, imported.board, imported.keys)?;
+            Ok(name)
+        });
+        match result {
+            Ok(name) => {
+                println!("Imported {}: {name}", path.display());
+                count += 1;
+            }
+            Err(err) => println!("Skipped {}: {err}", path.display()),
+        }
+    }
+    write_json("./layouts.json", &*LAYOUTS);
+    println!("Imported {count} of {} files", files.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::memory::LAYOUTS;
+
+    #[test]
+    fn test_import() {
+        let content = std::fs::read_to_string("./layouts/colemak-dh.json").unwrap();
+        let imported = import(Some("colemak-dh.json"), &content, None).unwrap();
+        let ll = &*LAYOUTS.get("colemak-dh");
+        assert_eq!(imported.format, Format::Json);
+        assert_eq!(imported.board, ll.board);
+        assert_eq!(imported.keys, ll.keys);
+
+        let matrix = "q w e r t  y u i o p\na s d f g  h j k l ;\nz x c v b  n m , . /";
+        let genkey = format!("QWERTY\n{matrix}\n0 1 2 3 3 4 4 5 6 7\n0 1 2 3 3 4 4 5 6 7\n0 1 2 3 3 4 4 5 6 7");
+        let kle = r#"[["~\n`","!\n1","@\n2"],[{"w":1.5},"Tab","Q","W","E","R","T","Y","U","I","O","P"],
+                      [{"w":1.75},"Caps Lock","A","S","D","F","G","H","J","K","L",":\n;"],
+                      [{"w":2.25},"Shift","Z","X","C","V","B","N","M","<\n,",">\n.","?\n/"]]"#;
+        let expected = import(None, matrix, None).unwrap();
+        assert_eq!(expected.board, "ortho");
+        let genkey = import(None, &genkey, None).unwrap();
+        assert_eq!((genkey.format, genkey.name.as_deref()), (Format::Genkey, Some("QWERTY")));
+        assert_eq!(genkey.keys, expected.keys);
+        let kle = import(None, kle, None).unwrap();
+        assert_eq!((kle.format, kle.board.as_str()), (Format::Kle, "stagger"));
+        assert_eq!(kle.keys, expected.keys);
+    }
+
+    #[test]
+    fn test_import_klc() {
+        let klc = "KBD\tsample\t\"Sample\"\n\
+                   \n\
+                   LAYOUT\t\t// a comment\n\
+                   10\tQ\t1\tq\tQ\n\
+                   11\tW\t1\t0077\t0057\t// w written in hex\n\
+                   1e\tA\t1\ta\tA\n\
+                   1f\tS\t1\t00e9@\t00c9\t// dead key\n\
+                   2c\tZ\t1\tz\tZ\n\
+                   39\tSPACE\t0\t0020\t0020\n\
+                   \n\
+                   KEYNAME\n\
+                   10\tnot-a-key\n\
+                   ENDKBD\n";
+        let imported = import(Some("sample.klc"), klc, None).unwrap();
+        assert_eq!((imported.format, imported.name.as_deref(), imported.board.as_str()), (Format::Klc, Some("sample"), "stagger"));
+        assert_eq!(imported.keys.len(), 5);
+        assert_eq!(imported.keys[&'q'], (0, 0, 0));
+        assert_eq!(imported.keys[&'w'], (0, 1, 1));
+        assert_eq!(imported.keys[&'é'], (1, 1, 1));
+        assert_eq!(imported.keys[&'z'], (2, 0, 0));
+        assert_eq!(import(Some("sample.klc"), klc, Some("angle")).unwrap().keys[&'z'].2, 1);
+
+        let collision = klc.replace("1e\tA\t1\ta", "1e\tA\t1\tQ");
+        assert!(import(Some("sample.klc"), &collision, None).is_err());
+    }
+}
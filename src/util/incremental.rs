@@ -0,0 +1,248 @@
+use crate::util::consts::TABLE;
+use crate::util::core::{Corpus, Finger, Key, Layout, Metric, Position, Stat};
+use fxhash::FxHashMap;
+
+const SPACE: Key = ' ';
+const NO_FINGER: Finger = Finger::MAX;
+
+/// Trigram corpus with its keys mapped to dense indices, shared by every layout analyzed on it
+pub struct CompiledCorpus {
+    keys: Vec<Key>,
+    indices: FxHashMap<Key, usize>,
+    /// Trigrams without spaces or repeated keys
+    trigrams: Vec<([usize; 3], u64)>,
+    /// Indices into `trigrams` of the trigrams containing each key
+    key_trigrams: Vec<Vec<usize>>,
+    sfr: u64,
+}
+
+impl CompiledCorpus {
+    pub fn new(grams: &Corpus<3>) -> Self {
+        let mut keys: Vec<Key> = Vec::new();
+        let mut indices: FxHashMap<Key, usize> = FxHashMap::default();
+        let mut merged: FxHashMap<[usize; 3], u64> = FxHashMap::default();
+        let mut sfr = 0u64;
+
+        for (gram, count) in grams.iter() {
+            if gram.contains(&SPACE) {
+                continue;
+            }
+            if gram[0] == gram[1] || gram[1] == gram[2] || gram[0] == gram[2] {
+                sfr += count;
+                continue;
+            }
+            let gram = gram.map(|key| *indices.entry(key).or_insert_with(|| {
+                keys.push(key);
+                keys.len() - 1
+            }));
+            *merged.entry(gram).or_insert(0) += count;
+        }
+
+        let mut trigrams: Vec<([usize; 3], u64)> = merged.into_iter().collect();
+        trigrams.sort_unstable();
+        let mut key_trigrams = vec![Vec::new(); keys.len()];
+        for (index, (gram, _)) in trigrams.iter().enumerate() {
+            gram.iter().for_each(|key| key_trigrams[*key].push(index));
+        }
+
+        CompiledCorpus {
+            keys,
+            indices,
+            trigrams,
+            key_trigrams,
+            sfr,
+        }
+    }
+}
+
+/// Trigram stats of a layout that are updated on every swap,
+/// by reclassifying only the trigrams that contain either of the swapped keys
+pub struct IncrementalAnalyzer<'a> {
+    corpus: &'a CompiledCorpus,
+    /// Keys of the corpus followed by the keys that only the layout has
+    keys: Vec<Key>,
+    indices: FxHashMap<Key, usize>,
+    positions: Vec<Option<Position>>,
+    fingers: Vec<Finger>,
+    counts: [u64; Metric::COUNT],
+    last_swap: Option<(usize, usize)>,
+}
+
+impl<'a> IncrementalAnalyzer<'a> {
+    pub fn new(corpus: &'a CompiledCorpus, layout: &Layout) -> Self {
+        let mut keys = corpus.keys.clone();
+        let mut indices = corpus.indices.clone();
+        let mut extra: Vec<Key> = layout.keys()
+            .filter(|key| !indices.contains_key(key))
+            .copied()
+            .collect();
+        extra.sort_unstable();
+        for key in extra {
+            indices.insert(key, keys.len());
+            keys.push(key);
+        }
+
+        let positions: Vec<Option<Position>> = keys.iter().map(|key| layout.get(key).copied()).collect();
+        let fingers: Vec<Finger> = positions.iter()
+            .map(|pos| pos.map_or(NO_FINGER, |(_, _, finger)| finger))
+            .collect();
+
+        let mut analyzer = IncrementalAnalyzer {
+            corpus,
+            keys,
+            indices,
+            positions,
+            fingers,
+            counts: [0; Metric::COUNT],
+            last_swap: None,
+        };
+        analyzer.counts[Metric::Sfr as usize] = corpus.sfr;
+        for (gram, count) in corpus.trigrams.iter() {
+            analyzer.counts[analyzer.classify(gram)] += count;
+        }
+        analyzer
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    pub fn index(&self, key: Key) -> Option<usize> {
+        self.indices.get(&key).copied()
+    }
+
+    pub fn position(&self, key: usize) -> Option<&Position> {
+        self.positions[key].as_ref()
+    }
+
+    pub fn counts(&self) -> &[u64; Metric::COUNT] {
+        &self.counts
+    }
+
+    /// Weighted sum of the share of each metric
+    pub fn score(&self, weights: &[f64; Metric::COUNT]) -> f64 {
+        let total = self.counts.iter().sum::<u64>();
+        if total == 0 {
+            return 0.0;
+        }
+        self.counts.iter().zip(weights.iter())
+            .map(|(count, weight)| *count as f64 * weight)
+            .sum::<f64>() / total as f64
+    }
+
+    /// Trigram stats of the current layout, identical to `analyzer::trigrams`
+    pub fn stats(&self) -> Stat {
+        let counter = Metric::new_counter().into_keys()
+            .map(|metric| (metric, self.counts[metric as usize]))
+            .collect();
+        Metric::normalize_counter(&counter)
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.keys.iter().zip(self.positions.iter())
+            .filter_map(|(key, pos)| Some((*key, (*pos)?)))
+            .collect()
+    }
+
+    /// Swaps the positions of two keys by index, returning the change in count of each metric
+    pub fn swap(&mut self, key0: usize, key1: usize) -> [i64; Metric::COUNT] {
+        let before = self.counts;
+        self.apply_swap(key0, key1);
+        self.last_swap = Some((key0, key1));
+
+        let mut deltas = [0i64; Metric::COUNT];
+        for (delta, (after, before)) in deltas.iter_mut().zip(self.counts.iter().zip(before.iter())) {
+            *delta = *after as i64 - *before as i64;
+        }
+        deltas
+    }
+
+    /// Undoes the last swap, returning the keys it swapped back
+    pub fn undo(&mut self) -> Option<(usize, usize)> {
+        let (key0, key1) = self.last_swap.take()?;
+        self.apply_swap(key0, key1);
+        Some((key0, key1))
+    }
+
+    pub fn last_swap(&self) -> Option<(usize, usize)> {
+        self.last_swap
+    }
+
+    fn apply_swap(&mut self, key0: usize, key1: usize) {
+        if key0 == key1 {
+            return;
+        }
+        self.update_counts(key0, key1, false);
+        self.positions.swap(key0, key1);
+        self.fingers.swap(key0, key1);
+        self.update_counts(key0, key1, true);
+    }
+
+    /// Adds or removes the trigrams affected by swapping two keys
+    fn update_counts(&mut self, key0: usize, key1: usize, add: bool) {
+        let corpus = self.corpus;
+        let empty: &[usize] = &[];
+        let trigrams0 = corpus.key_trigrams.get(key0).map_or(empty, Vec::as_slice);
+        let trigrams1 = corpus.key_trigrams.get(key1).map_or(empty, Vec::as_slice);
+
+        // Trigrams containing both keys are only counted once
+        let affected = trigrams0.iter()
+            .chain(trigrams1.iter().filter(|index| !corpus.trigrams[**index].0.contains(&key0)));
+        for index in affected {
+            let (gram, count) = &corpus.trigrams[*index];
+            let metric = self.classify(gram);
+            match add {
+                true => self.counts[metric] += count,
+                false => self.counts[metric] -= count,
+            }
+        }
+    }
+
+    #[inline]
+    fn classify(&self, [key0, key1, key2]: &[usize; 3]) -> usize {
+        let (finger0, finger1, finger2) = (self.fingers[*key0], self.fingers[*key1], self.fingers[*key2]);
+        if finger0 == NO_FINGER || finger1 == NO_FINGER || finger2 == NO_FINGER {
+            return Metric::Unknown as usize;
+        }
+        let finger_hash = (finger0 << 8) | (finger1 << 4) | finger2;
+        TABLE[usize::from(finger_hash)] as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::analyzer;
+    use crate::util::core::LayoutConfig;
+    use crate::util::corpora::corpus_ngrams;
+    use crate::util::memory::LAYOUTS;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_incremental_stats() {
+        let trigrams = corpus_ngrams::<3>("mt-quotes");
+        let corpus = CompiledCorpus::new(&trigrams);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for name in ["qwerty", "colemak-dh", "semimak"] {
+            let ll = &*LAYOUTS.get(name);
+            let mut analyzer = IncrementalAnalyzer::new(&corpus, &ll.keys);
+            assert_eq!(analyzer.stats(), analyzer::trigrams(ll, &trigrams), "{name}");
+
+            let placed: Vec<usize> = ll.keys.keys().filter_map(|key| analyzer.index(*key)).collect();
+            for _ in 0..20 {
+                let key0 = rng.gen_range(0..analyzer.keys().len());
+                let key1 = placed[rng.gen_range(0..placed.len())];
+                let before = *analyzer.counts();
+                analyzer.swap(key0, key1);
+                let swapped = LayoutConfig::new(String::new(), 0, ll.board.clone(), analyzer.layout());
+                assert_eq!(analyzer.stats(), analyzer::trigrams(&swapped, &trigrams), "{name}");
+
+                analyzer.undo();
+                assert_eq!(*analyzer.counts(), before, "{name}");
+                analyzer.swap(key0, key1);
+            }
+        }
+    }
+}
@@ -110,6 +110,7 @@ pub fn write_cached_stats(path: &str, cached_stats: &ServerCachedStats) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::core::Like;
 
     #[test]
     fn test_get_map_str_str() {
@@ -126,12 +127,21 @@ mod tests {
     }
 
     #[test]
-    fn test_get_map_str_vec_u64() {
+    fn test_get_map_str_vec_like() {
         let path = "./likes.json";
-        let map = read_json::<FxHashMap<String, Vec<u64>>>(path);
+        let map = read_json::<FxHashMap<String, Vec<Like>>>(path);
         dbg!(map);
     }
 
+    #[test]
+    fn test_like_serde() {
+        // Likes from before timing are bare user IDs
+        let json = r#"[1,{"user":2,"time":5}]"#;
+        let likes: Vec<Like> = serde_json::from_str(json).unwrap();
+        assert_eq!(likes, vec![Like { user: 1, time: None }, Like { user: 2, time: Some(5) }]);
+        assert_eq!(serde_json::to_string(&likes).unwrap(), json);
+    }
+
     #[test]
     fn test_get_map_u64_vec_str() {
         let path = "./authors.json";
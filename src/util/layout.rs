@@ -1,5 +1,13 @@
-use crate::util::{analyzer, authors, corpora, links, memory};
-use crate::util::core::{FingerUsage, LayoutConfig, Metric, Stat};
+use fxhash::FxHashMap;
+use crate::util::{analyzer, authors, corpora, links, memory, score};
+use crate::util::consts::{BOARDS, FINGER_NAMES, FMAP_ANGLE, FMAP_STANDARD, FREE_CHAR, GENKEY_FINGERS, NUMBER_ROW, THUMB_ROW};
+use crate::util::core::{unix_time, Col, Finger, FingerUsage, Key, Layout, LayoutConfig, Metadata, Metric, Position, Row, Stat};
+
+const MISSING_MIN_FREQ: f64 = 0.001;
+const MISSING_MAX_COUNT: usize = 10;
+/// Spaces that split the thumb row into the left and right thumb clusters
+const THUMB_GAP: usize = 3;
+const MAX_TAG_LEN: usize = 24;
 
 fn is_char_allowed_in_name(c: char) -> bool {
     matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' |
@@ -21,26 +29,372 @@ pub fn check_name(name: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Tags are lowercase words joined by dashes, like `vowel-left`
+pub fn check_tag(tag: &str) -> Result<(), String> {
+    let valid = !tag.is_empty() && tag.len() <= MAX_TAG_LEN
+        && tag.split('-').all(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+    match valid {
+        true => Ok(()),
+        false => Err(format!("Error: `{tag}` is not a valid tag, use lowercase words joined by dashes like `vowel-left`")),
+    }
+}
+
+/// Splits a matrix into its number row, if it has one, and its other rows, ignoring blank lines around it
+fn split_rows(matrix: &str) -> (Option<&str>, Vec<&str>) {
+    let mut rows = matrix.lines()
+        .skip_while(|row| row.trim().is_empty())
+        .collect::<Vec<_>>();
+    while rows.last().is_some_and(|row| row.trim().is_empty()) {
+        rows.pop();
+    }
+
+    // With four rows, the number row is told apart from the thumb row by being mostly digits
+    let number_row = match rows.first() {
+        Some(_) if rows.len() == 5 => Some(rows.remove(0)),
+        Some(row) if rows.len() == 4 && is_number_row(row) => Some(rows.remove(0)),
+        _ => None,
+    };
+    (number_row, rows)
+}
+
+fn is_number_row(row: &str) -> bool {
+    let keys: Vec<char> = row.chars().filter(|c| !c.is_whitespace()).collect();
+    keys.iter().filter(|c| c.is_ascii_digit()).count() * 2 > keys.len()
+}
+
+/// Leading whitespace of the three main rows of a matrix, where the first line of the matrix
+/// counts as not indented, since the code block starts right before it
+pub fn get_indents(matrix: &str) -> Vec<usize> {
+    let (number_row, rows) = split_rows(matrix);
+    let mut spaces: Vec<usize> = rows.iter().take(3)
+        .map(|row| row.chars().take_while(|c| c.is_whitespace()).count())
+        .collect();
+    if let (None, Some(first)) = (number_row, spaces.first_mut()) {
+        *first = 0;
+    }
+    spaces
+}
+
+/// Board type that the indentation of the rows of a matrix describes,
+/// where unindented rows with a wide gap between the hands are a split board
+fn detect_board(matrix: &str) -> Result<&'static str, String> {
+    let (_, rows) = split_rows(matrix);
+    let hand_gap = " ".repeat(THUMB_GAP);
+    let is_split = rows.iter().take(3).all(|row| row.trim().contains(&hand_gap));
+    let board = match get_indents(matrix)[..] {
+        [top, home, bottom] if top < home && home < bottom => "stagger",
+        [top, home, bottom] if top == home && bottom > 1 => "mini",
+        [top, home, bottom] if top == home && home < bottom => "angle",
+        [top, home, bottom] if top == home && home == bottom && is_split => "split",
+        [top, home, bottom] if top == home && home == bottom => "ortho",
+        _ => {
+            let indents: String = rows.iter().zip(get_indents(matrix)).enumerate()
+                .map(|(row, (keys, spaces))| {
+                    let plural = if spaces == 1 { "" } else { "s" };
+                    format!("\n  Row {}, indented by {spaces} space{plural}: `{}`", row + 1, keys.trim())
+                })
+                .collect();
+            return Err(format!("Error: board shape is undefined, the rows are indented as{indents}\n\
+                                Indent the rows by 0 0 0 spaces for ortho, 0 0 1 for angle, 0 0 2 for mini \
+                                or 0 1 2 for stagger, or 0 0 0 with {THUMB_GAP} spaces between the hands for split, \
+                                or give the board with `--board <board>`"));
+        }
+    };
+    Ok(board)
+}
+
+/// Reads the board type and keys of a matrix as written in `add`,
+/// where the board type is detected from the indentation of each row if it isn't given.
+/// The matrix may start with a number row, and end with a thumb row.
+pub fn parse_matrix(matrix: &str, board: Option<&str>) -> Result<(String, Layout), String> {
+    parse_template(matrix, board).map(|(board, keymap, _)| (board, keymap))
+}
+
+/// Reads a matrix like `parse_matrix`, where each `FREE_CHAR` leaves a gap in its row.
+/// Also returns the positions of the gaps, for `gen` to fill.
+pub fn parse_template(matrix: &str, board: Option<&str>) -> Result<(String, Layout, Vec<Position>), String> {
+    if let Some(board) = board.filter(|board| !BOARDS.contains(board)) {
+        return Err(format!("Error: unknown board `{board}`, expected one of: {}", BOARDS.join(", ")));
+    }
+    let (number_row, rows) = split_rows(matrix);
+    let row_count = rows.len();
+
+    if row_count < 3 {
+        return Err(format!("Expected 3 lines, got {}", row_count));
+    }
+
+    let board = match board {
+        Some(board) => board.to_owned(),
+        None => detect_board(matrix)?.to_owned(),
+    };
+
+    if row_count > 4 {
+        return Err(format!("Error: expected at most 3 rows and a thumb row, got {row_count} rows"));
+    }
+
+    let key_rows: Vec<Vec<Key>> = rows[..3].iter()
+        .map(|row| row.chars().filter(|c| !c.is_whitespace()).collect())
+        .collect();
+    let mut cells = main_cells(&key_rows, &board);
+    if let Some(number_row) = number_row {
+        let keys = number_row.chars().filter(|c| !c.is_whitespace());
+        cells.extend(keys.enumerate()
+            .map(|(col, ch)| (ch, (NUMBER_ROW, col as Col, FMAP_STANDARD[col.min(9)]))));
+    }
+    if let Some(thumb_row) = rows.get(3) {
+        cells.extend(place_thumbs(thumb_row)?);
+    }
+    let mut keymap = Layout::default();
+    let mut gaps: Vec<Position> = Vec::new();
+    for (ch, pos) in cells {
+        if ch == FREE_CHAR {
+            gaps.push(pos);
+        } else if keymap.insert(ch, pos).is_some() {
+            return Err(format!("Error: `{ch}` is defined twice"));
+        }
+    }
+    Ok((board, keymap, gaps))
+}
+
+/// Places the keys of the thumb row. A gap of at least `THUMB_GAP` spaces splits the row
+/// into the left and right thumb clusters, otherwise every key goes to the left thumb
+/// if the row is indented by more than 8 spaces, or else to the right thumb.
+fn place_thumbs(thumb_row: &str) -> Result<Vec<(Key, Position)>, String> {
+    let keys = |half: &str| -> Vec<Key> {
+        half.chars().filter(|c| !c.is_whitespace()).collect()
+    };
+    let indent = thumb_row.chars().take_while(|c| c.is_whitespace()).count();
+    match thumb_row.trim().split_once(&" ".repeat(THUMB_GAP)) {
+        Some((left, right)) => {
+            let (left, right) = (keys(left), keys(right));
+            if left.len() > 5 {
+                return Err(format!("Error: the left thumb cluster has {} keys, expected at most 5", left.len()));
+            }
+            // The clusters meet between the hands, at columns 4 and 5
+            let first_col = 5 - left.len();
+            let left = left.into_iter().enumerate()
+                .map(|(i, ch)| (ch, (THUMB_ROW, (first_col + i) as Col, 4)));
+            let right = right.into_iter().enumerate()
+                .map(|(i, ch)| (ch, (THUMB_ROW, (5 + i) as Col, 5)));
+            Ok(left.chain(right).collect())
+        }
+        None => {
+            let finger = if indent > 8 { 4 } else { 5 };
+            Ok(keys(thumb_row).into_iter().enumerate()
+                .map(|(i, ch)| (ch, (THUMB_ROW, i as Col, finger)))
+                .collect())
+        }
+    }
+}
+
+/// Places rows of keys with the standard fingers of the board, leaving a gap for each `FREE_CHAR`
+pub fn place_keys(rows: &[Vec<Key>], board: &str) -> Result<Layout, String> {
+    let mut keymap: Layout = Layout::default();
+    for (ch, pos) in main_cells(rows, board) {
+        if ch != FREE_CHAR && keymap.insert(ch, pos).is_some() {
+            return Err(format!("Error: `{ch}` is defined twice"));
+        }
+    }
+    Ok(keymap)
+}
+
+/// Positions of the keys of the main rows with the standard fingers of the board, gaps included
+fn main_cells(rows: &[Vec<Key>], board: &str) -> Vec<(Key, Position)> {
+    let mut cells: Vec<(Key, Position)> = Vec::new();
+    for (row_idx, row) in rows.iter().enumerate() {
+        for (col_idx, ch) in row.iter().enumerate() {
+            let fmap = if row_idx == 2 && board == "angle" {
+                &FMAP_ANGLE
+            } else {
+                &FMAP_STANDARD
+            };
+
+            let finger = fmap[col_idx.min(9)];
+
+            cells.push((*ch, (row_idx as u8, col_idx as u8, finger)));
+        }
+    }
+    cells
+}
+
+/// Fingers of each row of a layout, from 0 (LP) to 9 (RP), where 4 and 5 are the thumbs
+pub type Fingermap = Vec<Vec<Finger>>;
+
+/// Named fingermaps that `add` accepts in place of a fingermap block
+pub const FINGERMAP_PRESETS: [&str; 2] = ["standard", "angle"];
+
+pub fn fingermap_preset(name: &str) -> Option<Fingermap> {
+    match name {
+        "standard" => Some(vec![FMAP_STANDARD.to_vec(); 3]),
+        "angle" => Some(vec![FMAP_STANDARD.to_vec(), FMAP_STANDARD.to_vec(), FMAP_ANGLE.to_vec()]),
+        _ => None,
+    }
+}
+
+/// Fingermap given to `add` or `gen`, either as a preset with `--fingers` or as a fingermap block
+pub fn get_fingermap(preset: Option<&str>, block: Option<&str>) -> Result<Option<Fingermap>, String> {
+    match (preset, block) {
+        (Some(_), Some(_)) => Err("Error: use either `--fingers` or a fingermap block, not both".to_owned()),
+        (Some(preset), None) => match fingermap_preset(&preset.to_lowercase()) {
+            Some(fingermap) => Ok(Some(fingermap)),
+            None => Err(format!("Error: unknown fingering `{preset}`, expected one of: {}",
+                                FINGERMAP_PRESETS.join(", "))),
+        },
+        (None, Some(block)) => parse_fingermap(block.trim()).map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Preset that the fingers of a board follow when no fingermap is given
+pub fn default_fingering(board: &str) -> &'static str {
+    match board {
+        "angle" => "angle",
+        _ => "standard",
+    }
+}
+
+/// Reads a fingermap block such as `0123344567`, with a line per row. Fingers are numbered
+/// like genkey does, from 0 (LP) to 7 (RP), with 8 and 9 as the left and right thumbs.
+/// A single line is used for the three main rows, and a fifth line goes before them as the number row.
+pub fn parse_fingermap(block: &str) -> Result<Fingermap, String> {
+    let mut lines: Vec<&str> = block.lines().filter(|line| !line.trim().is_empty()).collect();
+    if lines.is_empty() || lines.len() > 5 {
+        return Err(format!("Error: a fingermap has 1 to 5 rows, got {}", lines.len()));
+    }
+    // Rows are stored in the order of their index, where the number row comes last
+    if lines.len() == 5 {
+        lines.rotate_left(1);
+    }
+    let fingermap = lines.iter().map(|line| {
+        line.chars().filter(|c| !c.is_whitespace()).map(|c| match c.to_digit(10) {
+            Some(finger) => Ok(GENKEY_FINGERS[finger as usize]),
+            None => Err(format!("Error: `{c}` is not a finger, expected 0 (LP) to 7 (RP), or 8 and 9 for the thumbs")),
+        }).collect::<Result<Vec<_>, _>>()
+    }).collect::<Result<Fingermap, _>>()?;
+
+    match fingermap.as_slice() {
+        [fingers] => Ok(vec![fingers.clone(); 3]),
+        _ => Ok(fingermap),
+    }
+}
+
+/// Finger of a column in a fingermap row, where the columns past its end use its last finger
+fn fingermap_finger(fingers: &[Finger], col: Col) -> Option<Finger> {
+    fingers.get(usize::from(col)).or(fingers.last()).copied()
+}
+
+/// Reassigns the fingers of the keys on the rows that the fingermap covers
+pub fn apply_fingermap(keymap: &mut Layout, fingermap: &Fingermap) -> Result<(), String> {
+    if let Some(row) = fingermap.iter().position(Vec::is_empty) {
+        return Err(format!("Error: row {} of the fingermap is empty", row + 1));
+    }
+    for (_, (row, col, finger)) in keymap.iter_mut() {
+        if let Some(fingers) = fingermap.get(usize::from(*row)) {
+            *finger = fingermap_finger(fingers, *col).unwrap();
+        }
+    }
+    Ok(())
+}
+
+/// Preset that the fingers of the main rows follow, or `None` if they are custom
+pub fn get_fingering(ll: &LayoutConfig) -> Option<&'static str> {
+    FINGERMAP_PRESETS.iter().copied().find(|name| {
+        let fingermap = fingermap_preset(name).unwrap();
+        ll.keys.values().all(|(row, col, finger)| match fingermap.get(usize::from(*row)) {
+            Some(fingers) => fingermap_finger(fingers, *col) == Some(*finger),
+            None => true,
+        })
+    })
+}
+
+/// Moves a layout to another board. Layouts with the default fingering
+/// of their old board take on the default fingering of the new one.
+pub fn set_board(ll: &mut LayoutConfig, board: &str) {
+    if get_fingering(ll) == Some(default_fingering(&ll.board)) {
+        let mut keys = ll.keys.clone();
+        let fingermap = fingermap_preset(default_fingering(board)).unwrap();
+        apply_fingermap(&mut keys, &fingermap).unwrap();
+        ll.set_keys(keys);
+    }
+    ll.board = board.to_owned();
+}
+
+/// Adds a new layout owned by `id`, with the same checks as `add`
+pub fn create(name: &str, id: u64, board: String, keys: Layout) -> Result<(), String> {
+    check_name(name)?;
+    let mut data = LayoutConfig::new(name.to_owned(), id, board, keys);
+    data.meta.created = Some(unix_time());
+    match memory::LAYOUTS.add(data) {
+        true => Ok(()),
+        false => Err(format!("Error: `{name}` already exists")),
+    }
+}
+
+/// Extra sections shown by `view`
+#[derive(Debug, Default)]
+pub struct ViewOptions {
+    pub fingers: bool,
+    /// Score preset, or the default preset if `None`
+    pub preset: Option<String>,
+}
+
 pub fn get_matrix_str(ll: &LayoutConfig) -> String {
-    let mut keyboard: [[char; 16]; 4]= [[' '; 16]; 4];
-    ll.keys.iter().for_each(|(key, pos)| {
+    let cells: Vec<(Position, char)> = ll.keys.iter().map(|(key, pos)| (*pos, *key)).collect();
+    get_grid_str(&ll.board, &cells)
+}
+
+/// Keys that are placed differently in `after` than in `before`, including new keys
+pub fn get_moved_keys(before: &Layout, after: &Layout) -> Vec<Key> {
+    let mut moved: Vec<Key> = after.iter()
+        .filter(|(key, (row, col, _))| before.get(key).map(|(row, col, _)| (row, col)) != Some((row, col)))
+        .map(|(key, _)| *key)
+        .collect();
+    moved.sort_unstable();
+    moved
+}
+
+/// Matrix of `after` that only shows the keys that moved since `before`
+pub fn get_moved_str(before: &Layout, after: &LayoutConfig) -> String {
+    let moved = get_moved_keys(before, &after.keys);
+    let cells: Vec<(Position, char)> = after.keys.iter()
+        .map(|(key, pos)| (*pos, if moved.contains(key) { *key } else { '·' }))
+        .collect();
+    get_grid_str(&after.board, &cells)
+}
+
+/// Matrix of the finger of each key, in the same shape as `get_matrix_str`
+/// and numbered like fingermap blocks
+pub fn get_fingermap_str(ll: &LayoutConfig) -> String {
+    let cells: Vec<(Position, char)> = ll.keys.values()
+        .map(|pos| {
+            let finger = GENKEY_FINGERS.iter().position(|finger| *finger == pos.2);
+            (*pos, finger.and_then(|finger| char::from_digit(finger as u32, 10)).unwrap_or('?'))
+        })
+        .collect();
+    get_grid_str(&ll.board, &cells)
+}
+
+fn get_grid_str(board: &str, cells: &[(Position, char)]) -> String {
+    let width = cells.iter().map(|((_, col, _), _)| usize::from(*col) + 1).fold(16, usize::max);
+    let mut keyboard: Vec<Vec<char>> = vec![vec![' '; width]; usize::from(NUMBER_ROW) + 1];
+    cells.iter().for_each(|(pos, key)| {
         let (row, col, _) = pos;
-        if *row > 4 || *col > 16 { return; }
+        if *row > NUMBER_ROW { return; }
         keyboard[usize::from(*row)][usize::from(*col)] = *key;
     });
 
-    // maximum length possible = 2(initial) + 2 + 5 * 2 + 2 + 11 * 2
     let mut rows: Vec<String> = std::iter::repeat_with(
-        || String::with_capacity(38))
-        .take(4)
+        || String::with_capacity(2 * width + 6))
+        .take(keyboard.len())
         .collect();
     rows.iter_mut().for_each(|row| { row.push_str("  ") });
 
-    match ll.board.as_str() {
+    match board {
         "angle" => { rows[2].push(' '); }
         "stagger" => { rows[1].push(' '); rows[2].push_str("  "); }
         _ => ()
     }
+    let hand_gap = if board == "split" { "    " } else { " " };
     keyboard.iter().enumerate().for_each(|(row, row_keys)| {
         let left_hand = &row_keys[..5];
         let right_hand = &row_keys[5..];
@@ -49,17 +403,82 @@ pub fn get_matrix_str(ll: &LayoutConfig) -> String {
             rows[row].push(*key);
             rows[row].push(' ');
         });
-        rows[row].push(' ');
+        rows[row].push_str(hand_gap);
         right_hand.iter().for_each(|key| {
             rows[row].push(*key);
             rows[row].push(' ');
         });
     });
 
-    match rows[3].chars().all(|c| c == ' ') {
-        true => rows[..3].join("\n"),
-        false => rows.join("\n")
+    // The number row goes on top, and the thumb row at the bottom if they have keys
+    let is_empty = |row: Row| keyboard[usize::from(row)].iter().all(|c| *c == ' ');
+    [NUMBER_ROW, 0, 1, 2, THUMB_ROW].into_iter()
+        .filter(|row| *row < THUMB_ROW || !is_empty(*row))
+        .map(|row| rows[usize::from(row)].as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Matrix of the layout in the format that `add` reads, indented by board type
+pub fn get_input_str(ll: &LayoutConfig) -> String {
+    let mut rows: Vec<Vec<(Col, Key)>> = vec![Vec::new(); usize::from(NUMBER_ROW) + 1];
+    let mut thumbs = [false; 2];
+    ll.keys.iter().for_each(|(key, (row, col, finger))| {
+        if let Some(keys) = rows.get_mut(usize::from(*row)) {
+            keys.push((*col, *key));
+        }
+        if *row == THUMB_ROW && (4..=5).contains(finger) {
+            thumbs[usize::from(*finger - 4)] = true;
+        }
+    });
+    rows.iter_mut().for_each(|keys| keys.sort_unstable());
+
+    // `add` reads the thumb row as the left thumb if it is indented by more than 8 spaces
+    let thumb_indent = if thumbs == [true, false] { 9 } else { 2 };
+    let indents = match ll.board.as_str() {
+        "stagger" => [0, 1, 2, thumb_indent],
+        "angle" => [0, 0, 1, thumb_indent],
+        "mini" => [0, 0, 2, thumb_indent],
+        _ => [0, 0, 0, thumb_indent],
+    };
+    // Split boards keep their hands apart by as much as `add` needs to detect them
+    let hand_gap = if ll.board == "split" { " ".repeat(THUMB_GAP) } else { "  ".to_owned() };
+    let join_keys = |keys: &[(Col, Key)], hand_gap: &str| -> String {
+        let mut line = String::new();
+        for (index, (col, key)) in keys.iter().enumerate() {
+            if index > 0 {
+                line.push_str(if *col == 5 { hand_gap } else { " " });
+            }
+            line.push(*key);
+        }
+        line
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    if !rows[usize::from(NUMBER_ROW)].is_empty() {
+        lines.push(join_keys(&rows[usize::from(NUMBER_ROW)], &hand_gap));
+    }
+    for (row, (keys, indent)) in rows.iter().zip(indents).enumerate() {
+        if keys.is_empty() {
+            continue;
+        }
+        let line = match (row == usize::from(THUMB_ROW), thumbs) {
+            // Split thumb clusters are separated by a wide gap
+            (true, [true, true]) => {
+                let split = keys.partition_point(|(col, _)| *col < 5);
+                let (left, right) = keys.split_at(split);
+                format!("{}{}{}", join_keys(left, "  "), " ".repeat(THUMB_GAP), join_keys(right, "  "))
+            }
+            (true, _) => join_keys(keys, "  "),
+            _ => join_keys(keys, &hand_gap),
+        };
+        lines.push(" ".repeat(indent) + &line);
+    }
+    // An empty thumb row keeps a number row without digits from being read as the thumb row
+    if lines.len() == 4 && rows[usize::from(THUMB_ROW)].is_empty() && !is_number_row(&lines[0]) {
+        lines.push(format!("  {FREE_CHAR}"));
     }
+    lines.join("\n")
 }
 
 pub fn get_stats_str(stats: &Stat, finger_usage: &FingerUsage) -> String {
@@ -103,36 +522,117 @@ pub fn get_stats_str(stats: &Stat, finger_usage: &FingerUsage) -> String {
 
     format!(
         "\
-  Alt: {alt:>5.2}%\n\
-  Rol: {roll:>5.2}%   (In/Out: {inroll:>5.2}% | {outroll:>5.2}%)\n\
-  One: {one:>5.2}%   (In/Out: {inone:>5.2}% | {outone:>5.2}%)\n\
-  Rtl: {rolltal:>5.2}%   (In/Out: {inrolltal:>5.2}% | {outrolltal:>5.2}%)\n\
-  Red: {red:>5.2}%   (Bad:    {bad_red:>5.2}%)\n\
+\x20 Alt: {alt:>5.2}%\n\
+\x20 Rol: {roll:>5.2}%   (In/Out: {inroll:>5.2}% | {outroll:>5.2}%)\n\
+\x20 One: {one:>5.2}%   (In/Out: {inone:>5.2}% | {outone:>5.2}%)\n\
+\x20 Rtl: {rolltal:>5.2}%   (In/Out: {inrolltal:>5.2}% | {outrolltal:>5.2}%)\n\
+\x20 Red: {red:>5.2}%   (Bad:    {bad_red:>5.2}%)\n\
 \n\
-  SFB: {sfb:>5.2}%\n\
-  SFS: {sfs:>5.2}%   (Red/Alt: {red_sfs:>5.2}% | {alt_sfs:>5.2}%)\n\
+\x20 SFB: {sfb:>5.2}%\n\
+\x20 SFS: {sfs:>5.2}%   (Red/Alt: {red_sfs:>5.2}% | {alt_sfs:>5.2}%)\n\
 \n\
-  LH/RH: {lh:>5.2}% | {rh:>5.2}%\n\
+\x20 LH/RH: {lh:>5.2}% | {rh:>5.2}%\n\
     ")
 }
 
+/// Named stats before and after a change to a layout, side by side
+pub fn get_stats_diff_str(before: &Stat, after: &Stat) -> String {
+    let mut s = format!("{:<8}{:>9}{:>9}{:>9}\n", "", "Before", "After", "Diff");
+    for (name, _) in analyzer::NAMED_STATS.iter() {
+        let before = analyzer::named_stat(before, name).unwrap() * 100.0;
+        let after = analyzer::named_stat(after, name).unwrap() * 100.0;
+        let diff = after - before;
+        s.push_str(&format!("{name:<8}{before:>8.2}%{after:>8.2}%{diff:>+8.2}%\n"));
+    }
+    s
+}
+
+pub fn get_coverage_str(stats: &Stat, missing: &[(Key, f64)]) -> String {
+    let unknown = stats.get(&Metric::Unknown).unwrap() * 100.0;
+    let missing: String = missing.iter()
+        .filter(|(_, freq)| *freq >= MISSING_MIN_FREQ)
+        .take(MISSING_MAX_COUNT)
+        .map(|(key, _)| format!(" {key}"))
+        .collect();
+
+    match missing.is_empty() {
+        true => format!("  Unk: {unknown:>5.2}%\n"),
+        false => format!("  Unk: {unknown:>5.2}%   (Missing:{missing})\n"),
+    }
+}
+
+pub fn get_fingers_str(usage: &FingerUsage, sfb: &FingerUsage, distance: &FingerUsage,
+                       rows: &FxHashMap<Row, f64>) -> String {
+    let mut s = "Finger".to_owned();
+    FINGER_NAMES.iter().for_each(|name| s.push_str(&format!("{name:>6}")));
+    s.push('\n');
+
+    // Distance is shown in keys per 100 bigrams, in line with the percentages
+    for (label, fingers) in [("Use%", usage), ("SFB%", sfb), ("Dist", distance)] {
+        s.push_str(&format!("{label:<6}"));
+        for finger in 0..FINGER_NAMES.len() as u16 {
+            let value = fingers.get(&finger).unwrap_or(&0.0) * 100.0;
+            s.push_str(&format!("{value:>6.2}"));
+        }
+        s.push('\n');
+    }
+
+    let get_row = |row: Row| -> f64 {
+        rows.get(&row).unwrap_or(&0.0) * 100.0
+    };
+    let (top, home, bottom, thumb) = (get_row(0), get_row(1), get_row(2), get_row(THUMB_ROW));
+    let number = match rows.contains_key(&NUMBER_ROW) {
+        true => format!("Num {:.2}% | ", get_row(NUMBER_ROW)),
+        false => String::new(),
+    };
+    s.push_str(&format!(
+        "Rows: {number}Top {top:.2}% | Home {home:.2}% | Bot {bottom:.2}% | Thumb {thumb:.2}%\n"
+    ));
+    s
+}
+
 pub fn to_string(ll: &LayoutConfig, id: u64) -> String {
+    to_string_with(ll, id, &ViewOptions::default())
+}
+
+pub fn to_string_with(ll: &LayoutConfig, id: u64, options: &ViewOptions) -> String {
     let author_reader = authors::AUTHORS.read().unwrap();
     let author = author_reader.get_name(ll.user).unwrap_or("Unknown");
     let monograms = corpora::ngrams::<1>(id);
     let trigrams = corpora::ngrams::<3>(id);
 
-    let matrix_str = get_matrix_str(ll);
+    let mut matrix_str = get_matrix_str(ll);
+    match get_fingering(ll) {
+        Some(fingering) if fingering == default_fingering(&ll.board) => (),
+        Some(fingering) => matrix_str.push_str(&format!("\nFingering: {fingering}")),
+        None => matrix_str.push_str(&format!("\nFingering: custom\n{}", get_fingermap_str(ll))),
+    }
+    let finger_usage = analyzer::fingers_usage(ll, &monograms);
+    if options.fingers {
+        let bigrams = corpora::ngrams::<2>(id);
+        let finger_sfb = analyzer::fingers_sfb(ll, &bigrams);
+        let finger_distance = analyzer::fingers_distance(ll, &bigrams);
+        let rows_usage = analyzer::rows_usage(ll, &monograms);
+        matrix_str.push_str("\n\n");
+        let fingers_str = get_fingers_str(&finger_usage, &finger_sfb, &finger_distance, &rows_usage);
+        matrix_str.push_str(fingers_str.trim_end());
+    }
 
     let stats = analyzer::trigrams(ll, &trigrams);
-    let finger_usage = analyzer::fingers_usage(ll, &monograms);
     let stats_str = get_stats_str(&stats, &finger_usage);
+    let missing = analyzer::missing_keys(ll, &monograms);
+    let coverage_str = get_coverage_str(&stats, &missing);
+    let score_str = match score::SCORE_MODELS.get(options.preset.as_deref()) {
+        Ok((preset, model)) => format!("Score: {:.2} ({preset})\n", model.score(ll, &stats, &monograms)),
+        Err(_) => String::new(),
+    };
 
-    let likes = memory::get_like_count(&ll.name);
+    let likes = memory::get_like_count(ll.id);
     let like_str = if likes == 1 {"like"} else {"likes"};
-    let external_link = links::get_link(&ll.name);
+    let external_link = links::get_link(ll.id);
+    let meta_str = get_meta_str(&ll.meta);
 
-    let ll_name = ll.name.as_str();
+    let ll_name = ll.title();
     let corpus_name = corpora::get_user_corpus(id).to_uppercase();
     format!("```\n\
              {ll_name} ({author}) ({likes} {like_str})\n\
@@ -140,6 +640,178 @@ pub fn to_string(ll: &LayoutConfig, id: u64) -> String {
              \n\
              {corpus_name}:\n\
              {stats_str}\
+             {coverage_str}\
+             \n\
+             {score_str}\
              ```\n\
+             {meta_str}\
              {external_link}\n")
-}
\ No newline at end of file
+}
+
+/// Description, tags, language and date added, a line each if set
+pub fn get_meta_str(meta: &Metadata) -> String {
+    let mut s = String::new();
+    if !meta.description.is_empty() {
+        s.push_str(&format!("{}\n", meta.description));
+    }
+    let mut details: Vec<String> = Vec::new();
+    if !meta.tags.is_empty() {
+        let tags: Vec<String> = meta.tags.iter().map(|tag| format!("`{tag}`")).collect();
+        details.push(format!("Tags: {}", tags.join(", ")));
+    }
+    if let Some(language) = &meta.language {
+        details.push(format!("Language: {language}"));
+    }
+    if let Some(created) = meta.created {
+        details.push(format!("Added <t:{created}:D>"));
+    }
+    if !details.is_empty() {
+        s.push_str(&format!("{}\n", details.join(" | ")));
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::conv;
+    use crate::util::memory::LAYOUTS;
+
+    #[test]
+    fn test_fingermap() {
+        let ll = &*LAYOUTS.get("qwerty");
+        assert_eq!(get_fingering(ll), Some("standard"));
+
+        assert_eq!(parse_fingermap("0123344567").unwrap(), fingermap_preset("standard").unwrap());
+        let mut keys = ll.keys.clone();
+        apply_fingermap(&mut keys, &parse_fingermap("0112344567").unwrap()).unwrap();
+        assert_eq!(keys[&'e'], (0, 2, 1));
+        assert_eq!(keys[&'y'], (0, 5, 6));
+        assert_eq!(keys[&'p'], (0, 9, 9));
+        let custom = LayoutConfig::new("custom".to_owned(), 0, ll.board.clone(), keys);
+        assert_eq!(get_fingering(&custom), None);
+        assert!(get_fingermap_str(&custom).starts_with("  0 1 1 2 3  4 4 5 6 7"));
+        assert_eq!(parse_fingermap("0123344567\n0123344567\n0123344567\n8899").unwrap()[3], vec![4, 4, 5, 5]);
+
+        let mut keys = ll.keys.clone();
+        apply_fingermap(&mut keys, &fingermap_preset("angle").unwrap()).unwrap();
+        let angle = LayoutConfig::new("angle".to_owned(), 0, ll.board.clone(), keys);
+        assert_eq!(get_fingering(&angle), Some("angle"));
+        assert!(parse_fingermap("01x").is_err());
+    }
+
+    #[test]
+    fn test_parse_template() {
+        // Gaps keep the columns of the keys after them, on every row
+        let (board, keys, gaps) = parse_template("1 ~ 3\nq ~ e r t y u i o p\na s d f g h j k l ;\n~ x c v b n m , . / [ ]\n  ~ ⌫", None).unwrap();
+        assert_eq!(board, "ortho");
+        assert_eq!(keys[&'e'], (0, 2, 2));
+        assert_eq!(keys[&'3'], (NUMBER_ROW, 2, 2));
+        assert_eq!(keys[&']'], (2, 11, 9));
+        assert_eq!(keys[&'⌫'], (THUMB_ROW, 1, 5));
+        assert_eq!(gaps, vec![(0, 1, 1), (2, 0, 0), (NUMBER_ROW, 1, 1), (THUMB_ROW, 0, 5)]);
+        assert!(!keys.contains_key(&FREE_CHAR));
+        assert!(parse_template("q w e\na s d\nz x c\nv b n\n1 2 3\nm", None).is_err());
+    }
+
+    #[test]
+    fn test_wide_matrix() {
+        let matrix = "1 2 3 4 5 6 7 8 9 0 - =\n\
+                      q w e r t y u i o p [ ] \\\n\
+                      a s d f g h j k l ; '\n\
+                      z x c v b n m , . /\n\
+                      \x20 ⇧ ⌫   ↵ ␣";
+        let (board, keys) = parse_matrix(matrix, None).unwrap();
+        assert_eq!(board, "ortho");
+        assert_eq!(keys[&'1'], (NUMBER_ROW, 0, 0));
+        assert_eq!(keys[&'\\'], (0, 12, 9));
+        assert_eq!(keys[&'⌫'], (THUMB_ROW, 4, 4));
+        assert_eq!(keys[&'↵'], (THUMB_ROW, 5, 5));
+
+        let ll = LayoutConfig::new("wide".to_owned(), 0, board, keys);
+        assert_eq!(parse_matrix(&get_input_str(&ll), None).unwrap(), (ll.board.clone(), ll.keys.clone()));
+
+        // A digit in the top row doesn't make it a number row
+        let (_, keys) = parse_matrix("q w e r t y u i o 1\na s d f g h j k l ;\nz x c v b n m , . /\n  ⌫   ␣", None).unwrap();
+        assert_eq!(keys[&'1'], (0, 9, 9));
+        assert_eq!(keys[&'⌫'].0, THUMB_ROW);
+        let (_, mut keys) = parse_matrix("! @ # $ %  ^ & * ( )\nq w e r t y u i o p\na s d f g h j k l ;\nz x c v b n m , . /\n~", None).unwrap();
+        assert_eq!(keys[&'!'], (NUMBER_ROW, 0, 0));
+        let symbols = LayoutConfig::new("symbols".to_owned(), 0, "ortho".to_owned(), keys.clone());
+        assert_eq!(parse_matrix(&get_input_str(&symbols), None).unwrap().1, keys);
+        assert!(parse_matrix("q w e r t y u i o p\na s d f g h j k l ;\nz x c v b n m , . /\n1 2 3 4 5 6    7", None).is_err());
+
+        apply_fingermap(&mut keys, &parse_fingermap("0011223344\n0123344567\n0123344567\n0123344567\n89").unwrap()).unwrap();
+        assert_eq!((keys[&'!'], keys[&'q']), ((NUMBER_ROW, 0, 0), (0, 0, 0)));
+        assert_eq!(keys[&'#'], (NUMBER_ROW, 2, 1));
+        assert!(parse_fingermap("0\n1\n2\n3\n4\n5").is_err());
+
+        let mut wide = ll.keys.clone();
+        wide.insert('§', (0, 20, 9));
+        let version = conv::packing_version(wide.values());
+        assert_eq!(version, conv::PACKING_V2);
+        assert_eq!(conv::layout::unpack(&conv::layout::pack(&wide, version), version), wide);
+    }
+
+    #[test]
+    fn test_board() {
+        let rows = ["q w e r t y u i o p", "a s d f g h j k l ;", "z x c v b n m , . /"];
+        let matrix = |indents: [usize; 3]| -> String {
+            rows.iter().zip(indents).map(|(row, indent)| " ".repeat(indent) + row).collect::<Vec<_>>().join("\n")
+        };
+        for (indents, board) in [([0, 0, 0], "ortho"), ([0, 0, 1], "angle"), ([0, 1, 2], "stagger"), ([0, 0, 2], "mini"), ([2, 2, 3], "stagger")] {
+            assert_eq!(parse_matrix(&matrix(indents), None).unwrap().0, board);
+        }
+        let err = parse_matrix(&matrix([0, 1, 0]), None).unwrap_err();
+        assert!(err.contains("Row 2, indented by 1 space:"), "{err}");
+        assert_eq!(parse_matrix(&matrix([0, 1, 0]), Some("ortho")).unwrap().0, "ortho");
+        assert_eq!(parse_matrix(&matrix([3, 1, 2]), None).unwrap().0, "stagger");
+        assert_eq!(parse_matrix(&matrix([0, 1, 0]), Some("split")).unwrap().0, "split");
+        assert!(parse_matrix(&matrix([0, 0, 0]), Some("round")).is_err());
+
+        // Split boards are told apart from ortho by the gap between the hands
+        let split = "q w e r t   y u i o p\na s d f g   h j k l ;\nz x c v b   n m , . /\n    ⌫ ␣   ↵ ⇧";
+        let (board, keys) = parse_matrix(split, None).unwrap();
+        assert_eq!((board.as_str(), keys[&'y']), ("split", (0, 5, 6)));
+        let ll = LayoutConfig::new("split".to_owned(), 0, board, keys);
+        assert_eq!(parse_matrix(&get_input_str(&ll), None).unwrap(), (ll.board.clone(), ll.keys.clone()));
+        assert!(get_matrix_str(&ll).starts_with("  q w e r t     y u i o p"));
+
+        let (board, keys) = parse_matrix(&matrix([0, 0, 1]), None).unwrap();
+        let mut ll = LayoutConfig::new("board".to_owned(), 0, board, keys);
+        let sum = ll.sum;
+        set_board(&mut ll, "ortho");
+        assert_eq!((ll.board.as_str(), get_fingering(&ll)), ("ortho", Some("standard")));
+        assert_ne!(ll.sum, sum);
+    }
+
+    #[test]
+    fn test_revision() {
+        let ll = LAYOUTS.get("qwerty");
+        let mut ll = LayoutConfig::new(ll.name.clone(), ll.user, ll.board.clone(), ll.keys.clone());
+        let (_, keys) = parse_matrix("q w e r t y u i o p\na s d f g h j k l ;\nx z c v b n m , . /", Some("ortho")).unwrap();
+        ll.push_revision(0);
+        ll.set_keys(keys);
+        assert_eq!(get_moved_keys(&ll.revision(1).unwrap().keys, &ll.keys), vec!['x', 'z']);
+        assert_eq!(ll.revision(2).unwrap().title(), "qwerty@2");
+        assert!(ll.revision(0).is_none() && ll.revision(3).is_none());
+
+        let json = serde_json::to_string(&ll).unwrap();
+        assert!(json.contains("\"history\""), "{json}");
+    }
+
+    #[test]
+    fn test_meta() {
+        assert!(check_tag("vowel-left").is_ok() && check_tag("3-row").is_ok());
+        assert!(check_tag("Vowel").is_err() && check_tag("vowel--left").is_err() && check_tag("-").is_err());
+
+        let ll = LAYOUTS.get("qwerty");
+        assert!(!serde_json::to_string(&*ll).unwrap().contains("\"meta\""));
+        let mut ll = LayoutConfig::new(ll.name.clone(), ll.user, ll.board.clone(), ll.keys.clone());
+        ll.meta.tags = vec!["vowel-left".to_owned()];
+        ll.meta.created = Some(0);
+        let json: crate::util::core::JsonLayoutConfig = serde_json::from_str(&serde_json::to_string(&ll).unwrap()).unwrap();
+        assert_eq!(json.meta, ll.meta);
+        assert!(get_meta_str(&ll.meta).contains("Tags: `vowel-left` | Added <t:0:D>"));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::util::consts::FINGER_NAMES;
+use crate::util::core::{Finger, FxIndexMap, JsonKey, JsonLayoutFile, Layout, LayoutConfig, LayoutId, Position};
+use crate::util::jsons::write_json;
+use crate::util::cache::CACHED_STATS;
+use crate::util::memory::{self, assign_ids, remove_layout_data, LAYOUTS};
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+pub const LAYOUTS_DIR: &str = "./layouts";
+
+/// Finger of a finger name, `TB` being an older name of the right thumb
+pub fn parse_finger(name: &str) -> Result<Finger, String> {
+    match name {
+        "TB" => Ok(5),
+        name => FINGER_NAMES.iter().position(|finger| *finger == name)
+            .map(|finger| finger as Finger)
+            .ok_or_else(|| format!("Error: `{name}` is not a finger")),
+    }
+}
+
+fn to_json_key((row, col, finger): &Position) -> JsonKey {
+    JsonKey {
+        row: *row,
+        col: *col,
+        finger: FINGER_NAMES[usize::from(*finger)].to_owned(),
+    }
+}
+
+fn from_json_key(pos: &JsonKey) -> Result<Position, String> {
+    Ok((pos.row, pos.col, parse_finger(&pos.finger)?))
+}
+
+pub fn to_file(ll: &LayoutConfig) -> JsonLayoutFile {
+    let mut keys: Vec<(char, Position)> = ll.keys.iter().map(|(key, pos)| (*key, *pos)).collect();
+    keys.sort_unstable_by_key(|(key, (row, col, _))| (*row, *col, *key));
+    let mut free = ll.free.clone();
+    free.sort_unstable();
+    JsonLayoutFile {
+        name: ll.title().to_owned(),
+        user: ll.user,
+        board: ll.board.clone(),
+        keys: keys.iter().map(|(key, pos)| (*key, to_json_key(pos))).collect(),
+        free: free.iter().map(to_json_key).collect(),
+        meta: ll.meta.clone(),
+    }
+}
+
+/// Layout of a file in `layouts/`, named after the file
+pub fn from_file(name: &str, file: &JsonLayoutFile) -> Result<LayoutConfig, String> {
+    let mut keys = Layout::default();
+    for (key, pos) in file.keys.iter() {
+        keys.insert(*key, from_json_key(pos)?);
+    }
+    let mut ll = LayoutConfig::new(name.to_owned(), file.user, file.board.clone(), keys);
+    ll.display_name = (file.name != name).then(|| file.name.clone());
+    let mut free: Vec<Position> = file.free.iter().map(from_json_key).collect::<Result<_, _>>()?;
+    free.sort_unstable();
+    ll.set_free(free);
+    ll.meta = file.meta.clone();
+    Ok(ll)
+}
+
+/// Reads every layout file of the directory, sorted by name
+pub fn read_dir(dir: &Path) -> Result<Vec<LayoutConfig>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|err| format!("Error: {}: {err}", dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths.iter().map(|path| {
+        let name = path.file_stem().and_then(|stem| stem.to_str())
+            .ok_or_else(|| format!("Error: {} is not a valid layout name", path.display()))?;
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Error: {}: {err}", path.display()))?;
+        let file: JsonLayoutFile = serde_json::from_str(&content)
+            .map_err(|err| format!("Error: {}: {err}", path.display()))?;
+        from_file(name, &file).map_err(|err| format!("{err} in {}", path.display()))
+    }).collect()
+}
+
+/// Writes a file per layout, removing the files of layouts that no longer exist.
+/// Returns the names of the removed files.
+pub fn write_dir(dir: &Path, layouts: &FxIndexMap<String, LayoutConfig>) -> Result<Vec<String>, String> {
+    std::fs::create_dir_all(dir).map_err(|err| format!("Error: {}: {err}", dir.display()))?;
+    for ll in layouts.values() {
+        let path = dir.join(format!("{}.json", ll.name));
+        let file = File::create(&path).map_err(|err| format!("Error: {}: {err}", path.display()))?;
+        let mut serializer = serde_json::Serializer::with_formatter(file, PrettyFormatter::with_indent(b"    "));
+        to_file(ll).serialize(&mut serializer).map_err(|err| format!("Error: {}: {err}", path.display()))?;
+    }
+
+    let mut removed: Vec<String> = Vec::new();
+    let entries = std::fs::read_dir(dir).map_err(|err| format!("Error: {}: {err}", dir.display()))?;
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else { continue };
+        if path.extension().is_some_and(|ext| ext == "json") && !layouts.contains_key(name) {
+            std::fs::remove_file(&path).map_err(|err| format!("Error: {}: {err}", path.display()))?;
+            removed.push(name.to_owned());
+        }
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Drift {
+    OnlyInDir(String),
+    OnlyInJson(String),
+    Differs(String, Vec<&'static str>),
+}
+
+impl Display for Drift {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Drift::OnlyInDir(name) => write!(f, "{name}: only in layouts/"),
+            Drift::OnlyInJson(name) => write!(f, "{name}: only in layouts.json"),
+            Drift::Differs(name, fields) => write!(f, "{name}: {} differ", fields.join(", ")),
+        }
+    }
+}
+
+/// Differences between the layouts read from `layouts/` and those of `layouts.json`
+pub fn find_drift(files: &[LayoutConfig], layouts: &FxIndexMap<String, LayoutConfig>) -> Vec<Drift> {
+    let mut drift: Vec<Drift> = Vec::new();
+    for file in files.iter() {
+        let Some(ll) = layouts.get(&file.name) else {
+            drift.push(Drift::OnlyInDir(file.name.clone()));
+            continue;
+        };
+        let fields: Vec<&'static str> = [
+            ("name", file.display_name != ll.display_name),
+            ("user", file.user != ll.user),
+            ("board", file.board != ll.board),
+            ("keys", file.keys != ll.keys),
+            ("free", file.free != ll.free),
+            ("meta", file.meta != ll.meta),
+        ].into_iter().filter_map(|(field, differs)| differs.then_some(field)).collect();
+        if !fields.is_empty() {
+            drift.push(Drift::Differs(file.name.clone(), fields));
+        }
+    }
+    for name in layouts.keys() {
+        if !files.iter().any(|file| file.name == *name) {
+            drift.push(Drift::OnlyInJson(name.clone()));
+        }
+    }
+    drift
+}
+
+/// `layouts export|load|check [dir]`, converting between `layouts.json` and `layouts/`
+pub fn layouts_main(args: &[String]) {
+    let action = args.first().map(String::as_str).unwrap_or("check");
+    let dir = PathBuf::from(args.get(1).map(String::as_str).unwrap_or(LAYOUTS_DIR));
+    let result = match action {
+        "export" => export_dir(&dir),
+        "load" => load_dir(&dir),
+        "check" => check_dir(&dir),
+        _ => Err(format!("Error: unknown action `{action}`, expected export, load or check")),
+    };
+    if let Err(err) = result {
+        println!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn export_dir(dir: &Path) -> Result<(), String> {
+    let layouts = LAYOUTS.read().unwrap();
+    let removed = write_dir(dir, &layouts)?;
+    removed.iter().for_each(|name| println!("Removed {name}"));
+    println!("Exported {} layouts to {}", layouts.len(), dir.display());
+    Ok(())
+}
+
+fn load_dir(dir: &Path) -> Result<(), String> {
+    let files = read_dir(dir)?;
+    let removed: Vec<LayoutId> = {
+        let mut layouts = LAYOUTS.write().unwrap();
+        let removed = layouts.values()
+            .filter(|ll| !files.iter().any(|file| file.name == ll.name))
+            .map(|ll| ll.id)
+            .collect();
+        layouts.retain(|name, _| files.iter().any(|file| file.name == *name));
+        for mut ll in files.into_iter() {
+            // The files don't keep the id and history of a layout
+            if let Some(old) = layouts.get_mut(&ll.name) {
+                ll.id = old.id;
+                ll.history = std::mem::take(&mut old.history);
+            }
+            layouts.insert(ll.name.clone(), ll);
+        }
+        assign_ids(&mut layouts);
+        println!("Loaded {} layouts from {}", layouts.len(), dir.display());
+        removed
+    };
+    // The likes, links and cached stats of the removed layouts would otherwise go to the next new layout
+    removed.into_iter().for_each(remove_layout_data);
+    memory::sync_data();
+    write_json("./cached_stats.json", &*CACHED_STATS);
+    Ok(())
+}
+
+fn check_dir(dir: &Path) -> Result<(), String> {
+    let files = read_dir(dir)?;
+    let layouts = LAYOUTS.read().unwrap();
+    let drift = find_drift(&files, &layouts);
+    drift.iter().for_each(|drift| println!("{drift}"));
+    match drift.len() {
+        0 => {
+            println!("layouts.json and {} are in sync", dir.display());
+            Ok(())
+        }
+        count => Err(format!("{count} layouts drifted between layouts.json and {}", dir.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layouts_dir() {
+        let files = read_dir(Path::new(LAYOUTS_DIR)).unwrap();
+        let layouts = LAYOUTS.read().unwrap();
+        assert_eq!(find_drift(&files, &layouts), Vec::new());
+
+        let round_trip: Vec<LayoutConfig> = layouts.values().map(|ll| {
+            let file = serde_json::to_string(&to_file(ll)).unwrap();
+            let file: JsonLayoutFile = serde_json::from_str(&file).unwrap();
+            from_file(&ll.name, &file).unwrap()
+        }).collect();
+        assert_eq!(find_drift(&round_trip, &layouts), Vec::new());
+    }
+}
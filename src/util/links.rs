@@ -1,17 +1,128 @@
+use crate::util::core::LayoutId;
 use crate::util::jsons::read_json;
 use fxhash::FxHashMap;
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
 
-pub(super) static LINKS: Lazy<Arc<RwLock<FxHashMap<String, String>>>> = Lazy::new(||
+pub const MAX_LINKS: usize = 5;
+const MAX_URL_LEN: usize = 300;
+
+/// Links of each layout by layout id
+pub(super) static LINKS: Lazy<Arc<RwLock<FxHashMap<String, Links>>>> = Lazy::new(||
     Arc::new(RwLock::new(read_json("./links.json")))
 );
 
-pub fn get_link(layout_name: &str) -> String {
+/// Links of a layout, stored as a plain string when there is only one
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(from = "JsonLinks", into = "JsonLinks")]
+pub struct Links(pub Vec<String>);
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum JsonLinks {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<JsonLinks> for Links {
+    fn from(links: JsonLinks) -> Self {
+        match links {
+            JsonLinks::One(link) => Links(vec![link]),
+            JsonLinks::Many(links) => Links(links),
+        }
+    }
+}
+
+impl From<Links> for JsonLinks {
+    fn from(Links(mut links): Links) -> Self {
+        match links.len() {
+            1 => JsonLinks::One(links.remove(0)),
+            _ => JsonLinks::Many(links),
+        }
+    }
+}
+
+pub fn get_link(id: LayoutId) -> String {
+    let links: Vec<String> = get_links(id).iter().map(|link| format!("<{}>", link)).collect();
+    links.join("\n")
+}
+
+pub fn get_links(id: LayoutId) -> Vec<String> {
     let links = LINKS.read().unwrap();
-    let exteral_link = links.get(layout_name);
-    match exteral_link {
-        Some(link) => format!("<{}>", link),
-        None => String::new()
+    links.get(&id.to_string()).map(|Links(links)| links.clone()).unwrap_or_default()
+}
+
+/// Accepts absolute http(s) urls with a host, like `https://example.com/layout`
+pub fn check_url(url: &str) -> Result<(), String> {
+    let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(|| format!("Error: `{url}` is not a link, links start with `https://`"))?;
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = host.rsplit_once(':').map_or(host, |(host, port)| {
+        if port.chars().all(|c| c.is_ascii_digit()) { host } else { "" }
+    });
+    let valid_host = host.contains('.') && !host.starts_with('.') && !host.ends_with('.')
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if !valid_host {
+        return Err(format!("Error: `{url}` does not have a valid host"));
+    }
+    if url.len() > MAX_URL_LEN || url.chars().any(|c| c.is_whitespace() || c == '<' || c == '>') {
+        return Err(format!("Error: `{url}` is not a valid link"));
+    }
+    Ok(())
+}
+
+pub fn add_link(id: LayoutId, layout_name: &str, url: &str) -> Result<usize, String> {
+    check_url(url)?;
+    let mut links = LINKS.write().unwrap();
+    let Links(layout_links) = links.entry(id.to_string()).or_default();
+    if layout_links.iter().any(|link| link == url) {
+        return Err(format!("Error: `{layout_name}` already links to <{url}>"));
+    }
+    if layout_links.len() >= MAX_LINKS {
+        return Err(format!("Error: `{layout_name}` already has {MAX_LINKS} links, remove one with `unlink`"));
+    }
+    layout_links.push(url.to_owned());
+    Ok(layout_links.len())
+}
+
+/// Removes a link of a layout, or all of them if `url` is `None`. Returns the removed links.
+pub fn remove_links(id: LayoutId, layout_name: &str, url: Option<&str>) -> Result<Vec<String>, String> {
+    let key = id.to_string();
+    let mut links = LINKS.write().unwrap();
+    let Some(Links(layout_links)) = links.get_mut(&key) else {
+        return Err(format!("Error: `{layout_name}` has no links"));
+    };
+    let removed = match url {
+        None => std::mem::take(layout_links),
+        Some(url) => match layout_links.iter().position(|link| link == url) {
+            Some(index) => vec![layout_links.remove(index)],
+            None => return Err(format!("Error: `{layout_name}` does not link to <{url}>")),
+        },
+    };
+    if layout_links.is_empty() {
+        links.remove(&key);
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links() {
+        assert!(check_url("https://oxey.dev/noctum").is_ok());
+        assert!(check_url("http://localhost:8080").is_err());
+        assert!(check_url("https://example.com:8080/a?b#c").is_ok());
+        assert!(check_url("oxey.dev/noctum").is_err());
+        assert!(check_url("https://exa mple.com").is_err());
+        assert!(check_url("https://.com").is_err());
+
+        let one: Links = serde_json::from_str("\"https://oxey.dev/noctum\"").unwrap();
+        assert_eq!(serde_json::to_string(&one).unwrap(), "\"https://oxey.dev/noctum\"");
+        let many: Links = serde_json::from_str("[\"https://a.dev\",\"https://b.dev\"]").unwrap();
+        assert_eq!(many.0.len(), 2);
+        assert_eq!(serde_json::to_string(&many).unwrap(), "[\"https://a.dev\",\"https://b.dev\"]");
     }
-}
\ No newline at end of file
+}
@@ -1,23 +1,32 @@
 use crate::util::authors::AUTHORS;
 use crate::util::conv;
-use crate::util::core::{FxIndexMap, JsonLayoutConfig, LayoutConfig};
+use crate::util::cache::CACHED_STATS;
+use crate::util::core::{FxIndexMap, JsonLayoutConfig, LayoutConfig, LayoutId, Revision, ServerLikes};
 use crate::util::corpora::CORPORA_PREFS;
 use crate::util::jsons::{read_json, write_json};
 use crate::util::links::LINKS;
-use fxhash::{FxBuildHasher, FxHashMap};
+use fxhash::FxBuildHasher;
 use once_cell::sync::Lazy;
 use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::borrow::Cow;
 use std::fmt::{Debug, Formatter};
 use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use strsim::jaro_winkler;
 use thiserror::Error;
 use crate::util::get::{Get, GetMut};
 
 pub static LAYOUTS: Lazy<ServerLayouts> = Lazy::new(|| read_json("./layouts.json"));
-pub static LIKES: Lazy<Arc<RwLock<FxHashMap<String, Vec<u64>>>>> = Lazy::new(|| read_json("./likes.json"));
+/// Likes of each layout by layout id, locked after `LAYOUTS` when both are needed
+pub static LIKES: Lazy<ServerLikes> = Lazy::new(|| read_json("./likes.json"));
+/// Highest id ever given to a layout, so that the ids of removed layouts are not reused
+static LAST_ID: Lazy<AtomicU64> = Lazy::new(||
+    AtomicU64::new(std::fs::read_to_string("./last_id.json").ok()
+        .and_then(|last_id| last_id.trim().parse().ok())
+        .unwrap_or(0))
+);
 
 #[derive(Debug, Error)]
 pub enum RemoveError<'a> {
@@ -27,20 +36,103 @@ pub enum RemoveError<'a> {
     NotOwner(&'a str),
 }
 
-pub fn get_like_count(name: &str) -> usize {
+/// Names at least this similar to the searched name count as a match
+const MIN_SIMILARITY: f64 = 0.9;
+/// Names at least this similar are trusted by the commands that change something
+const CONFIDENT_SIMILARITY: f64 = 0.98;
+/// A match is ambiguous when another layout is at most this much less similar
+const AMBIGUITY_MARGIN: f64 = 0.01;
+/// Names at least this similar are suggested when nothing matches
+const MIN_SUGGESTED_SIMILARITY: f64 = 0.75;
+const MAX_SUGGESTIONS: usize = 3;
+
+/// How a searched name matched a layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Match {
+    Exact,
+    /// Same name, ignoring case, spaces and punctuation
+    Normalized,
+    /// Similar name, with its jaro-winkler similarity
+    Fuzzy(f64),
+}
+
+/// Lowercase letters and digits of a name, so that `Colemak DH` is `colemak-dh`
+pub fn normalize_name(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Layout found by `ServerLayouts::find`
+pub struct Found<'a> {
+    ll: Get<'a, LayoutConfig>,
+    pub how: Match,
+}
+
+impl Found<'_> {
+    /// Tells which layout is shown when it was not named exactly
+    pub fn note(&self, name: &str) -> String {
+        match self.how {
+            Match::Fuzzy(_) => format!("Showing `{}`, the closest layout to `{}`\n", self.ll.name, name.trim()),
+            Match::Exact | Match::Normalized => String::new(),
+        }
+    }
+}
+
+impl Deref for Found<'_> {
+    type Target = LayoutConfig;
+
+    fn deref(&self) -> &Self::Target {
+        &self.ll
+    }
+}
+
+pub fn get_like_count(id: LayoutId) -> usize {
     let likes = LIKES.read().unwrap();
-    match likes.get(name) {
+    match likes.get(&id.to_string()) {
         Some(liked_users) => liked_users.len(),
         None => 0,
     }
 }
 
+/// New id, above the ids of every layout and every entry of the stores,
+/// in case the last id was not saved
+fn next_id(layouts: &FxIndexMap<String, LayoutConfig>) -> LayoutId {
+    let stored = |keys: &mut dyn Iterator<Item = &String>| -> LayoutId {
+        keys.filter_map(|key| key.parse::<LayoutId>().ok()).max().unwrap_or(0)
+    };
+    let used = [
+        layouts.values().map(|ll| ll.id).max().unwrap_or(0),
+        stored(&mut LIKES.read().unwrap().keys()),
+        stored(&mut LINKS.read().unwrap().keys()),
+        stored(&mut CACHED_STATS.read().unwrap().keys()),
+    ];
+    LAST_ID.fetch_max(used.into_iter().max().unwrap(), Ordering::SeqCst);
+    LAST_ID.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+/// Gives an id to the layouts without one, in order
+pub fn assign_ids(layouts: &mut FxIndexMap<String, LayoutConfig>) {
+    let missing: Vec<String> = layouts.values().filter(|ll| ll.id == 0).map(|ll| ll.name.clone()).collect();
+    for name in missing {
+        let id = next_id(layouts);
+        layouts[&name].id = id;
+    }
+}
+
+/// Drops the likes, links and cached stats of a removed layout
+pub fn remove_layout_data(id: LayoutId) {
+    let key = id.to_string();
+    LIKES.write().unwrap().remove(&key);
+    LINKS.write().unwrap().remove(&key);
+    CACHED_STATS.write().unwrap().shift_remove(&key);
+}
+
 pub fn sync_data() {
     write_json("./authors.json", &*AUTHORS);
     write_json("./corpora.json", &*CORPORA_PREFS);
     write_json("./layouts.json", &*LAYOUTS);
     write_json("./likes.json", &*LIKES);
     write_json("./links.json", &*LINKS);
+    write_json("./last_id.json", &LAST_ID.load(Ordering::SeqCst));
 }
 
 #[repr(transparent)]
@@ -56,11 +148,15 @@ impl Deref for ServerLayouts {
 }
 
 impl ServerLayouts {
-    pub fn add(&self, ll: LayoutConfig) -> bool {
+    /// Adds a layout, giving it a new id unless it already has one
+    pub fn add(&self, mut ll: LayoutConfig) -> bool {
         if self.contains(&ll.name) {
             return false;
         }
         let mut layouts_mut = self.write().unwrap();
+        if ll.id == 0 {
+            ll.id = next_id(&layouts_mut);
+        }
         layouts_mut.insert(ll.name.clone(), ll);
         true
     }
@@ -70,9 +166,21 @@ impl ServerLayouts {
     pub fn get_mut<'a>(&'a self, name: &'a str) -> GetMut<'a, LayoutConfig> {
         GetMut(self.write().unwrap(), Cow::Borrowed(name))
     }
-    pub fn find(&self, name: &str) -> Get<LayoutConfig> {
-        let closest = self.best_match(name);
-        Get(self.read().unwrap(), Cow::Owned(closest))
+    /// Finds the layout named like `name`, or errors with the closest names if none is close enough
+    pub fn find(&self, name: &str) -> Result<Found<'_>, String> {
+        self.find_with(name, MIN_SIMILARITY)
+    }
+    /// Same as `find`, but only accepts names that are near certain to mean the layout,
+    /// for commands that change something
+    pub fn find_confident(&self, name: &str) -> Result<Found<'_>, String> {
+        self.find_with(name, CONFIDENT_SIMILARITY)
+    }
+    fn find_with(&self, name: &str, min_similarity: f64) -> Result<Found<'_>, String> {
+        let (closest, how) = self.best_match(name, min_similarity)?;
+        Ok(Found {
+            ll: Get(self.read().unwrap(), Cow::Owned(closest)),
+            how,
+        })
     }
     pub fn contains(&self, name: &str) -> bool {
         let layouts = self.read().unwrap();
@@ -101,20 +209,40 @@ impl ServerLayouts {
             Err(RemoveError::NotOwner(name))
         }
     }
-    pub fn best_match(&self, base_name: &str) -> String {
+    /// Tries the exact name, then the name without case and punctuation, then similar names
+    pub fn best_match(&self, base_name: &str, min_similarity: f64) -> Result<(String, Match), String> {
         let layouts = self.read().unwrap();
-        let mut max_score = 0.0;
-        let mut closest = String::new();
-
-        for name in layouts.keys() {
-            let score = jaro_winkler(name, base_name);
+        let base_name = base_name.trim().to_lowercase();
+        if layouts.contains_key(&base_name) {
+            return Ok((base_name, Match::Exact));
+        }
+        let normalized = normalize_name(&base_name);
+        let same: Vec<&String> = layouts.keys().filter(|name| normalize_name(name) == normalized).collect();
+        if let [name] = same[..] {
+            return Ok((name.clone(), Match::Normalized));
+        }
 
-            if score > max_score {
-                max_score = score;
-                closest = name.to_string();
+        // Names that are the same once normalized are only told apart by their similarity
+        let mut ranked: Vec<(&String, f64)> = match same.is_empty() {
+            true => layouts.keys().map(|name| (name, jaro_winkler(name, &base_name))).collect(),
+            false => same.into_iter().map(|name| (name, jaro_winkler(name, &base_name))).collect(),
+        };
+        ranked.sort_by(|(name0, score0), (name1, score1)| score1.total_cmp(score0).then_with(|| name0.cmp(name1)));
+        if let [(name, score), rest @ ..] = &ranked[..] {
+            let ambiguous = rest.first().is_some_and(|(_, other_score)| *other_score > score - AMBIGUITY_MARGIN);
+            if *score >= min_similarity && !ambiguous {
+                return Ok(((*name).clone(), Match::Fuzzy(*score)));
             }
         }
-        closest
+        let suggestions: Vec<String> = ranked.iter()
+            .take_while(|(_, score)| *score >= MIN_SUGGESTED_SIMILARITY)
+            .take(MAX_SUGGESTIONS)
+            .map(|(name, _)| format!("`{name}`"))
+            .collect();
+        match suggestions.is_empty() {
+            true => Err(format!("Error: no layout is called `{base_name}`")),
+            false => Err(format!("Error: no layout is called `{base_name}`, did you mean: {}", suggestions.join(", "))),
+        }
     }
 
 }
@@ -147,15 +275,61 @@ impl<'de> Visitor<'de> for ServerLayoutsVisitor {
         );
         while let Some((key, ll)) = map.next_entry::<String, JsonLayoutConfig>()? {
             let name = key.clone();
+            let version = ll.packing.unwrap_or(conv::PACKING_V1);
             let layout_config = LayoutConfig {
+                id: ll.id,
                 name: key,
                 user: ll.user,
                 board: ll.board.clone(),
-                keys: conv::layout::unpack(&ll.keys),
+                keys: conv::layout::unpack(&ll.keys, version),
                 sum: conv::hash_keys(&ll.keys),
+                display_name: ll.name,
+                free: conv::positions::unpack(&ll.free, version),
+                history: ll.history.into_iter().map(Revision::from_json).collect(),
+                meta: ll.meta,
             };
             map_inner.insert(name, layout_config);
         }
+        assign_ids(&mut map_inner);
         Ok(ServerLayouts(Arc::new(RwLock::new(map_inner))))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        assert_eq!(LAYOUTS.find("Semimak").unwrap().how, Match::Exact);
+        let found = LAYOUTS.find("Colemak DH").unwrap();
+        assert_eq!((found.name.as_str(), found.how), ("colemak-dh", Match::Normalized));
+        drop(found);
+        assert!(matches!(LAYOUTS.find("semimka").unwrap().how, Match::Fuzzy(_)));
+        let err = LAYOUTS.find_confident("semimka").err().unwrap();
+        assert!(err.contains("did you mean: `semimak`"), "{err}");
+        assert!(LAYOUTS.find("zzzzzzz").is_err());
+    }
+
+    #[test]
+    fn test_next_id() {
+        let layouts = LAYOUTS.read().unwrap();
+        let highest = layouts.values().map(|ll| ll.id).max().unwrap();
+        let like_keys = LIKES.read().unwrap().keys().filter_map(|key| key.parse::<LayoutId>().ok()).max().unwrap();
+        let id = next_id(&layouts);
+        assert!(id > highest && id > like_keys);
+        assert!(next_id(&layouts) > id);
+    }
+
+    #[test]
+    fn test_sum_after_reload() {
+        let keys = LAYOUTS.get("qwerty").keys.clone();
+        let mut ll = LayoutConfig::new("reload".to_owned(), 0, "ortho".to_owned(), keys);
+        // A free position past column 15 packs the keys with a wider version
+        ll.set_free(vec![(0, 20, 9)]);
+        assert_eq!(ll.packing_version(), conv::PACKING_V2);
+        let json = serde_json::to_string(&FxIndexMap::from_iter([(ll.name.clone(), &ll)])).unwrap();
+        let reloaded: ServerLayouts = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.read().unwrap()["reload"].sum, ll.sum);
+    }
+}
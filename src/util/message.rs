@@ -1,6 +1,7 @@
 use std::fmt::{Debug, Formatter};
 use std::ops::Deref;
 use serenity::model::channel::Message as DiscordMessage;
+use crate::util::core::Attachment;
 use crate::util::parser::split_word;
 
 pub struct Message<'a> {
@@ -9,6 +10,8 @@ pub struct Message<'a> {
     pub action: &'a str,
     pub arg: &'a str,
     pub id: u64,
+    /// Downloaded attachments, only for commands that read them
+    pub files: Vec<Attachment>,
 }
 
 impl<'a> From<&'a DiscordMessage> for Message<'a> {
@@ -30,6 +33,7 @@ impl<'a> From<&'a DiscordMessage> for Message<'a> {
             action,
             arg,
             id,
+            files: Vec::new(),
         }
     }
 }
@@ -1,14 +1,23 @@
 pub mod analyzer;
 pub mod authors;
 pub mod cache;
+pub mod consistency;
 pub mod consts;
 pub mod core;
 pub mod corpora;
+pub mod export;
+pub mod heatmap;
+pub mod image;
+pub mod import;
+pub mod incremental;
 pub mod jsons;
 pub mod layout;
+pub mod layout_files;
 pub mod links;
 pub mod memory;
+pub mod optimizer;
 pub mod parser;
+pub mod score;
 mod conv;
 mod message;
 mod get;
@@ -33,4 +42,6 @@ pub fn validate_json() {
     assert!(!reader.is_empty());
     let reader = memory::LIKES.read().unwrap();
     assert!(!reader.is_empty());
+    let reader = &*score::SCORE_MODELS;
+    assert!(!reader.is_empty());
 }
@@ -0,0 +1,214 @@
+use crate::util::analyzer::named_stat;
+use crate::util::core::{Corpus, Layout, Metric, Stat};
+use crate::util::incremental::{CompiledCorpus, IncrementalAnalyzer};
+use crate::util::score::ScoreModel;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+/// Score of a layout that is updated on every swap, on top of an incremental analyzer
+pub struct IncrementalScorer<'a> {
+    model: &'a ScoreModel,
+    analyzer: IncrementalAnalyzer<'a>,
+    coefs: [f64; Metric::COUNT],
+    key_freqs: Vec<f64>,
+    freq_total: f64,
+    effort: f64,
+}
+
+impl<'a> IncrementalScorer<'a> {
+    pub fn new(model: &'a ScoreModel, corpus: &'a CompiledCorpus, layout: &Layout, monograms: &Corpus<1>) -> Self {
+        let analyzer = IncrementalAnalyzer::new(corpus, layout);
+        let mut key_freqs = vec![0.0; analyzer.keys().len()];
+        for ([key], count) in monograms.iter() {
+            if let Some(index) = analyzer.index(*key) {
+                key_freqs[index] += *count as f64;
+            }
+        }
+
+        let mut scorer = IncrementalScorer {
+            model,
+            analyzer,
+            coefs: get_coefs(model),
+            key_freqs,
+            freq_total: 0.0,
+            effort: 0.0,
+        };
+        (0..scorer.analyzer.keys().len()).for_each(|key| scorer.add_key(key, 1.0));
+        scorer
+    }
+
+    pub fn analyzer(&self) -> &IncrementalAnalyzer<'a> {
+        &self.analyzer
+    }
+
+    pub fn score(&self) -> f64 {
+        let effort = if self.freq_total > 0.0 { self.effort / self.freq_total } else { 0.0 };
+        self.analyzer.score(&self.coefs) * 100.0 + self.model.effort_weight * effort
+    }
+
+    /// Swaps the positions of two keys by index
+    pub fn swap(&mut self, key0: usize, key1: usize) {
+        self.add_keys(key0, key1, -1.0);
+        self.analyzer.swap(key0, key1);
+        self.add_keys(key0, key1, 1.0);
+    }
+
+    /// Undoes the last swap
+    pub fn undo(&mut self) {
+        if let Some((key0, key1)) = self.analyzer.last_swap() {
+            self.add_keys(key0, key1, -1.0);
+            self.analyzer.undo();
+            self.add_keys(key0, key1, 1.0);
+        }
+    }
+
+    fn add_keys(&mut self, key0: usize, key1: usize, sign: f64) {
+        self.add_key(key0, sign);
+        if key0 != key1 {
+            self.add_key(key1, sign);
+        }
+    }
+
+    /// Adds or removes the effort of a key, only counting keys that the layout places
+    #[inline]
+    fn add_key(&mut self, key: usize, sign: f64) {
+        if let Some(pos) = self.analyzer.position(key) {
+            let freq = self.key_freqs[key];
+            self.freq_total += sign * freq;
+            self.effort += sign * freq * self.model.key_effort(pos);
+        }
+    }
+}
+
+/// Weight of each metric's share, derived from the named stats in the score model
+fn get_coefs(model: &ScoreModel) -> [f64; Metric::COUNT] {
+    let mut coefs = [0.0; Metric::COUNT];
+    for (metric, coef) in coefs.iter_mut().enumerate() {
+        // Named stats are linear in metrics, so the weight of a metric is the stat of that metric alone
+        let stats: Stat = Stat::from_iter([(Metric::unpack(metric as u8), 1.0)]);
+        *coef = model.metrics.iter()
+            .map(|(name, weight)| weight * named_stat(&stats, name).unwrap())
+            .sum();
+    }
+    coefs
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Budget {
+    pub iterations: u64,
+    pub time: Duration,
+}
+
+#[derive(Debug)]
+pub struct AnnealResult {
+    pub layout: Layout,
+    pub score: f64,
+    pub iterations: u64,
+    pub elapsed: Duration,
+}
+
+/// Maximizes the score with simulated annealing, only moving the keys in `free`
+pub fn anneal(scorer: &mut IncrementalScorer, free: &[usize], budget: Budget) -> AnnealResult {
+    let start = Instant::now();
+    let mut rng = StdRng::from_entropy();
+    if free.len() < 2 {
+        return AnnealResult {
+            layout: scorer.analyzer().layout(),
+            score: scorer.score(),
+            iterations: 0,
+            elapsed: start.elapsed(),
+        };
+    }
+    let pick = |rng: &mut StdRng| -> (usize, usize) {
+        let index0 = rng.gen_range(0..free.len());
+        let index1 = (index0 + rng.gen_range(1..free.len())) % free.len();
+        (free[index0], free[index1])
+    };
+
+    // Random starting point
+    for _ in 0..free.len() * 4 {
+        let (key0, key1) = pick(&mut rng);
+        scorer.swap(key0, key1);
+    }
+
+    // Start hot enough to accept most worse swaps
+    let mut score = scorer.score();
+    let mut sample = 0.0;
+    const SAMPLES: usize = 200;
+    for _ in 0..SAMPLES {
+        let (key0, key1) = pick(&mut rng);
+        scorer.swap(key0, key1);
+        sample += (scorer.score() - score).abs();
+        scorer.undo();
+    }
+    let start_temp = (sample / SAMPLES as f64 / -(0.8f64.ln())).max(f64::EPSILON);
+    let end_temp = start_temp * 1e-4;
+
+    let mut best_layout = scorer.analyzer().layout();
+    let mut best_score = score;
+    let mut iterations = 0u64;
+    let mut progress = 0.0;
+    while iterations < budget.iterations && progress < 1.0 {
+        // Checking the time on every iteration is comparatively slow
+        if iterations.is_multiple_of(1024) {
+            let time_progress = start.elapsed().as_secs_f64() / budget.time.as_secs_f64();
+            progress = time_progress.max(iterations as f64 / budget.iterations as f64);
+        }
+        let temp = start_temp * (end_temp / start_temp).powf(progress);
+
+        let (key0, key1) = pick(&mut rng);
+        scorer.swap(key0, key1);
+        let new_score = scorer.score();
+        let delta = new_score - score;
+        if delta >= 0.0 || rng.gen::<f64>() < (delta / temp).exp() {
+            score = new_score;
+            if score > best_score {
+                best_score = score;
+                best_layout = scorer.analyzer().layout();
+            }
+        } else {
+            scorer.undo();
+        }
+        iterations += 1;
+    }
+
+    AnnealResult {
+        layout: best_layout,
+        score: best_score,
+        iterations,
+        elapsed: start.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::analyzer;
+    use crate::util::core::LayoutConfig;
+    use crate::util::corpora::corpus_ngrams;
+    use crate::util::memory::LAYOUTS;
+    use crate::util::score::SCORE_MODELS;
+
+    #[test]
+    fn test_incremental_score() {
+        let monograms = corpus_ngrams::<1>("mt-quotes");
+        let trigrams = corpus_ngrams::<3>("mt-quotes");
+        let (_, model) = SCORE_MODELS.get(None).unwrap();
+        let ll = &*LAYOUTS.get("qwerty");
+        let corpus = CompiledCorpus::new(&trigrams);
+        let mut scorer = IncrementalScorer::new(model, &corpus, &ll.keys, &monograms);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..50 {
+            let key0 = rng.gen_range(0..scorer.analyzer().keys().len());
+            let key1 = rng.gen_range(0..scorer.analyzer().keys().len());
+            scorer.swap(key0, key1);
+
+            let swapped = LayoutConfig::new(String::new(), 0, ll.board.clone(), scorer.analyzer().layout());
+            let stats = analyzer::trigrams(&swapped, &trigrams);
+            let expected = model.score(&swapped, &stats, &monograms);
+            assert!((scorer.score() - expected).abs() < 1e-9, "{} != {expected}", scorer.score());
+        }
+    }
+}
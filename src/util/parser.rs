@@ -92,15 +92,6 @@ pub fn get_args(s: &str) -> Vec<&str> {
     s.split_whitespace().collect()
 }
 
-pub fn get_layout(s: &str) -> (String, String) {
-    let parts: Vec<&str> = s.split("```").collect();
-
-    let name = parts.first().unwrap_or(&"").trim().to_lowercase();
-    let matrix = parts.get(1).unwrap_or(&"").trim().to_lowercase();
-
-    (name, matrix)
-}
-
 pub fn get_kwargs(s: &str, cmd_kwargs: &FxHashMap<String, KwargType>)
                   -> Result<KwargData, ParseKwargError> {
     let words: Vec<&str> = s.split_whitespace().collect();
@@ -181,6 +172,31 @@ pub fn map_from_vec(v: Vec<(&str, KwargType)>) -> FxHashMap<String, KwargType> {
         .collect()
 }
 
+/// Unix time of the start of a `YYYY-MM-DD` day, in UTC
+pub fn parse_date(date: &str) -> Result<u64, String> {
+    let err = || format!("Error: `{date}` is not a date, expected YYYY-MM-DD");
+    let fields: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = fields[..] else { return Err(err()) };
+    let (Ok(year), Ok(month), Ok(day)) = (year.parse::<i64>(), month.parse::<i64>(), day.parse::<i64>()) else {
+        return Err(err());
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_len = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return Err(err()),
+    };
+    if year < 1970 || !(1..=month_len).contains(&day) {
+        return Err(err());
+    }
+    // Days since 1970-01-01, counting years from March so that the leap day comes last
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let days = 365 * year + year / 4 - year / 100 + year / 400 + (153 * month + 2) / 5 + day - 1 - 719468;
+    Ok(days as u64 * 86400)
+}
+
 fn starts_with_kw_prefix(word: &str) -> bool {
     ["--", "—", "––"].iter().any(|prefix| word.starts_with(prefix))
 }
@@ -246,5 +262,15 @@ mod test {
         let not_kwargs = get_kwargs("hello all --other --flag", &cmd_kwargs);
         assert!(not_kwargs.is_err());
     }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-01"), Ok(0));
+        assert_eq!(parse_date("2000-03-01"), Ok(951868800));
+        assert_eq!(parse_date("2024-02-29"), Ok(1709164800));
+        assert!(parse_date("2023-02-29").is_err());
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("yesterday").is_err());
+    }
 }
 
@@ -0,0 +1,155 @@
+use crate::util::analyzer::{named_stat, NAMED_STATS};
+use crate::util::core::{Corpus, FxIndexMap, LayoutConfig, Position, Stat};
+use crate::util::jsons::read_json;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+pub static SCORE_MODELS: Lazy<ScoreModels> = Lazy::new(||
+    ScoreModels::from_json(read_json("./scores.json"))
+);
+
+#[derive(Debug, Deserialize)]
+struct JsonScoreConfig {
+    default: String,
+    effort: Vec<Vec<f64>>,
+    strength: [f64; 10],
+    presets: FxIndexMap<String, JsonScorePreset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonScorePreset {
+    metrics: FxIndexMap<String, f64>,
+    effort_weight: f64,
+    #[serde(default)]
+    effort: Option<Vec<Vec<f64>>>,
+    #[serde(default)]
+    strength: Option<[f64; 10]>,
+}
+
+/// Weighted sum of stats and key effort, where higher scores are better
+#[derive(Debug, Clone)]
+pub struct ScoreModel {
+    pub metrics: FxIndexMap<String, f64>,
+    pub effort_weight: f64,
+    /// Effort of each key by row and column
+    pub effort: Vec<Vec<f64>>,
+    /// Strength of each finger, which divides the effort of its keys
+    pub strength: [f64; 10],
+}
+
+impl ScoreModel {
+    pub fn score(&self, ll: &LayoutConfig, stats: &Stat, monograms: &Corpus<1>) -> f64 {
+        let metrics = self.metrics.iter()
+            .map(|(name, weight)| weight * named_stat(stats, name).unwrap() * 100.0)
+            .sum::<f64>();
+        metrics + self.effort_weight * self.effort(ll, monograms)
+    }
+
+    /// Average effort per keystroke, ignoring keys that the layout doesn't place
+    pub fn effort(&self, ll: &LayoutConfig, monograms: &Corpus<1>) -> f64 {
+        let mut total = 0u64;
+        let mut effort = 0.0;
+
+        for ([gram], count) in monograms.iter() {
+            let Some(pos) = ll.keys.get(gram) else {
+                continue;
+            };
+            effort += *count as f64 * self.key_effort(pos);
+            total += count;
+        }
+        match total {
+            0 => 0.0,
+            _ => effort / total as f64,
+        }
+    }
+
+    /// Effort of a single key, where keys outside the effort grid take the maximum effort
+    pub fn key_effort(&self, (row, col, finger): &Position) -> f64 {
+        let key_effort = self.effort.get(usize::from(*row))
+            .and_then(|row| row.get(usize::from(*col)))
+            .copied()
+            .unwrap_or_else(|| self.effort.iter().flatten().fold(0.0, |max: f64, effort| max.max(*effort)));
+        let strength = self.strength.get(usize::from(*finger)).unwrap_or(&1.0);
+        key_effort / strength
+    }
+}
+
+#[derive(Debug)]
+pub struct ScoreModels {
+    pub default: String,
+    pub presets: FxIndexMap<String, ScoreModel>,
+}
+
+impl ScoreModels {
+    /// # Panics
+    /// This function will panic if:
+    /// - The default preset does not exist
+    /// - A preset weighs an unknown stat
+    #[track_caller]
+    fn from_json(json: JsonScoreConfig) -> Self {
+        let presets: FxIndexMap<String, ScoreModel> = json.presets.into_iter()
+            .map(|(name, preset)| {
+                if let Some(metric) = preset.metrics.keys()
+                    .find(|metric| !NAMED_STATS.iter().any(|(stat, _)| stat == metric)) {
+                    panic!("Invalid metric `{metric}` in score preset `{name}`");
+                }
+                let model = ScoreModel {
+                    metrics: preset.metrics,
+                    effort_weight: preset.effort_weight,
+                    effort: preset.effort.unwrap_or_else(|| json.effort.clone()),
+                    strength: preset.strength.unwrap_or(json.strength),
+                };
+                (name, model)
+            })
+            .collect();
+        assert!(presets.contains_key(&json.default), "Default score preset `{}` does not exist", json.default);
+        ScoreModels {
+            default: json.default,
+            presets,
+        }
+    }
+
+    /// Gets a preset by name, or the default preset if `name` is `None`
+    pub fn get(&self, name: Option<&str>) -> Result<(&str, &ScoreModel), String> {
+        let name = name.unwrap_or(&self.default).to_lowercase();
+        match self.presets.get_key_value(&name) {
+            Some((name, model)) => Ok((name, model)),
+            None => {
+                let names: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+                Err(format!("Error: unknown score preset `{name}`, expected one of: {}", names.join(", ")))
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.presets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::analyzer;
+    use crate::util::corpora::corpus_ngrams;
+    use crate::util::memory::LAYOUTS;
+
+    #[test]
+    fn test_score_presets() {
+        let monograms = corpus_ngrams::<1>("mt-quotes");
+        let trigrams = corpus_ngrams::<3>("mt-quotes");
+        let qwerty = &*LAYOUTS.get("qwerty");
+        let colemak_dh = &*LAYOUTS.get("colemak-dh");
+        let qwerty_stats = analyzer::trigrams(qwerty, &trigrams);
+        let colemak_dh_stats = analyzer::trigrams(colemak_dh, &trigrams);
+
+        for (name, model) in SCORE_MODELS.presets.iter() {
+            let qwerty_score = model.score(qwerty, &qwerty_stats, &monograms);
+            let colemak_dh_score = model.score(colemak_dh, &colemak_dh_stats, &monograms);
+            assert!(colemak_dh_score > qwerty_score, "{name}: {colemak_dh_score} <= {qwerty_score}");
+        }
+    }
+}